@@ -0,0 +1,41 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use device_detection_51deg::{
+    evidence::{Evidence, EvidenceKind},
+    hash_engine::{HashConfig, HashEngine, HashEngineBuilder},
+};
+
+/// Reads the evidence of the records in the test data of the submodule.
+pub fn load_cases() -> Vec<Evidence> {
+    let test_data =
+        fs::read_to_string("device-detection-cxx/device-detection-data/20000 Evidence Records.yml")
+            .expect("reading the evidence records to work");
+
+    test_data
+        .split("---")
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let record: HashMap<String, String> = serde_yaml::from_str(part).unwrap();
+            record
+                .iter()
+                .try_fold(Evidence::default(), |evidence, (key, value)| {
+                    let field = key
+                        .strip_prefix("header.")
+                        .expect("all hints should be headers");
+                    evidence.try_add(EvidenceKind::HeaderString, field, value)
+                })
+                .expect("the records to contain no NUL characters")
+        })
+        .collect()
+}
+
+/// Builds an engine for the Lite data file of the submodule.
+pub fn engine(config: HashConfig) -> HashEngine {
+    HashEngineBuilder::new(Path::new(
+        "device-detection-cxx/device-detection-data/51Degrees-LiteV4.1.hash",
+    ))
+    .hash_config(config)
+    .init()
+    .inspect_err(|e| eprintln!("error building the engine: {e}"))
+    .expect("building the engine should work")
+}
@@ -0,0 +1,49 @@
+mod common;
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use device_detection_51deg::{evidence::Evidence, hash_engine::HashConfig};
+
+pub fn criterion_benchmark(c: &mut Criterion) {
+    let cases_evidence = common::load_cases();
+    let max_len = cases_evidence
+        .iter()
+        .map(Evidence::len)
+        .max()
+        .unwrap_or_default();
+
+    let hash_engine = common::engine(HashConfig::HighPerformance);
+
+    let mut group = c.benchmark_group("reused_results");
+    for count in [1usize, 100, 1000, 10_000, 20_000] {
+        let case: Vec<&Evidence> = cases_evidence.iter().take(count).collect();
+        group.throughput(criterion::Throughput::Elements(case.len() as u64));
+        group.bench_with_input(BenchmarkId::new("process", case.len()), &case, |b, case| {
+            b.iter(|| {
+                for evidence in case.iter() {
+                    let _result = hash_engine
+                        .process(evidence)
+                        .expect("processing evidence to work");
+                }
+            });
+        });
+        group.bench_with_input(
+            BenchmarkId::new("process_into", case.len()),
+            &case,
+            |b, case| {
+                let mut results = hash_engine
+                    .create_results(max_len)
+                    .expect("allocating results to work");
+                b.iter(|| {
+                    for evidence in case.iter() {
+                        hash_engine
+                            .process_into(&mut results, evidence)
+                            .expect("processing evidence to work");
+                    }
+                });
+            },
+        );
+    }
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
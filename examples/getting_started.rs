@@ -2,37 +2,37 @@ use anyhow::Result;
 use std::{path::PathBuf, str::FromStr};
 
 use device_detection_51deg::{
-    evidence::{Evidence, EvidenceKind},
+    evidence::{Evidence, EvidenceError, EvidenceKind},
     hash_engine::HashEngineBuilder,
 };
 
-fn demo_evidence() -> Vec<Evidence> {
-    vec![
+fn demo_evidence() -> Result<Vec<Evidence>, EvidenceError> {
+    Ok(vec![
         // A User-Agent from a mobile device.
-        Evidence::new_with_user_agent(
+        Evidence::try_new_with_user_agent(
             "Mozilla/5.0 (Linux; Android 9; SAMSUNG SM-G960U AppleWebKit/537.36 (KHTML, like Gecko) SamsungBrowser/10.1 Chrome/71.0.3578.99 Mobile Safari/537.36",
-        ),
+        )?,
         // A User-Agent from a desktop device.
-        Evidence::new_with_user_agent(
+        Evidence::try_new_with_user_agent(
             "Mozilla / 5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/78.0.3904.108 Safari/537.36",
-        ),
+        )?,
         // Evidence values from a windows 11 device using a browser
         // that supports User-Agent Client Hints.
-        Evidence::new_with_user_agent(
+        Evidence::try_new_with_user_agent(
             "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/98.0.4758.102 Safari/537.36",
-        )
-        .add(EvidenceKind::HeaderString, "sec-ch-ua-mobile", "?0")
-        .add(EvidenceKind::HeaderString, "sec-ch-ua", "\" Not A; Brand\";v=\"99\", \"Chromium\";v=\"98\", \"Google Chrome\";v=\"98\"")
-        .add(EvidenceKind::HeaderString, "sec-ch-ua-platform", "Windows")
-        .add(EvidenceKind::HeaderString, "sec-ch-ua-platform-version", "\"14.0.0\""),
-        Evidence::new_with_user_agent(
+        )?
+        .try_add(EvidenceKind::HeaderString, "sec-ch-ua-mobile", "?0")?
+        .try_add(EvidenceKind::HeaderString, "sec-ch-ua", "\" Not A; Brand\";v=\"99\", \"Chromium\";v=\"98\", \"Google Chrome\";v=\"98\"")?
+        .try_add(EvidenceKind::HeaderString, "sec-ch-ua-platform", "Windows")?
+        .try_add(EvidenceKind::HeaderString, "sec-ch-ua-platform-version", "\"14.0.0\"")?,
+        Evidence::try_new_with_user_agent(
             "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/98.0.4758.102 Safari/537.36"
-        )
-        .add(EvidenceKind::HeaderString,  "sec-ch-ua-mobile", "?0")
-        .add(EvidenceKind::HeaderString,  "sec-ch-ua", "\" Not A; Brand\";v=\"99\", \"Chromium\";v=\"98\", \"Google Chrome\";v=\"98\"")
-        .add(EvidenceKind::HeaderString,   "sec-ch-ua-platform", "Windows")
-        .add(EvidenceKind::HeaderString,    "sec-ch-ua-platform-version", "\"14.0.0\""),
-    ]
+        )?
+        .try_add(EvidenceKind::HeaderString, "sec-ch-ua-mobile", "?0")?
+        .try_add(EvidenceKind::HeaderString, "sec-ch-ua", "\" Not A; Brand\";v=\"99\", \"Chromium\";v=\"98\", \"Google Chrome\";v=\"98\"")?
+        .try_add(EvidenceKind::HeaderString, "sec-ch-ua-platform", "Windows")?
+        .try_add(EvidenceKind::HeaderString, "sec-ch-ua-platform-version", "\"14.0.0\"")?,
+    ])
 }
 
 fn main() -> Result<()> {
@@ -44,9 +44,9 @@ fn main() -> Result<()> {
         .init()?;
 
     let mut device_id = String::default();
-    for (i, mut evidence) in demo_evidence().into_iter().enumerate() {
+    for (i, mut evidence) in demo_evidence()?.into_iter().enumerate() {
         if i == 3 {
-            evidence = evidence.add(EvidenceKind::Query, "51D_deviceId", &device_id);
+            evidence = evidence.try_add(EvidenceKind::Query, "51D_deviceId", &device_id)?;
         }
 
         let mut result = manager.process(&evidence)?;
@@ -58,22 +58,18 @@ fn main() -> Result<()> {
         println!("Input:");
         println!("\t {:?}", evidence);
         println!("Results:");
-        result
-            .get_str("IsMobile")
-            .inspect(|s| println!("\tMobile Device: {}", s));
-
-        result
-            .get_str("PlatformName")
-            .inspect(|s| println!("\tPlatform Name: {}", s));
-        result
-            .get_str("PlatformVersion")
-            .inspect(|s| println!("\tPlatform Version {:?}", s));
-        result
-            .get_str("BrowserName")
-            .inspect(|s| println!("\tBrowser Name: {}", s));
-        result
-            .get_str("BrowserVersion")
-            .inspect(|s| println!("\tBrowser Version: {}", s));
+        for (label, property) in [
+            ("Mobile Device", "IsMobile"),
+            ("Platform Name", "PlatformName"),
+            ("Platform Version", "PlatformVersion"),
+            ("Browser Name", "BrowserName"),
+            ("Browser Version", "BrowserVersion"),
+        ] {
+            match result.get_str(property) {
+                Ok(value) => println!("\t{}: {}", label, value),
+                Err(reason) => println!("\t{}: no value, {}", label, reason),
+            }
+        }
     }
     Ok(())
 }
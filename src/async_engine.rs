@@ -0,0 +1,245 @@
+use std::{
+    error::Error,
+    fmt::Display,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use tokio::{
+    sync::Semaphore,
+    task::{self, JoinError},
+};
+
+use crate::{
+    device::Device,
+    device_info::DeviceInfo,
+    evidence::Evidence,
+    hash_engine::{HashEngine, HashManagerError, ResultsHash},
+};
+
+/// Processes evidence from async code without blocking the tokio workers.
+///
+/// Engines that read from the data file, like the `LowMemory` and `Balanced` configs,
+/// process on the blocking thread pool of tokio. At most `max_concurrency` evidence are
+/// processed at the same time, further calls wait for a permit instead of failing for lack
+/// of a file handle. Engines that hold the whole data set in memory process inline, as that
+/// never blocks on IO. This is checked for every call, as reloads can switch the engine
+/// between a file and memory.
+///
+/// Dropping the future of a call that waits for a permit, or whose work has not been
+/// started by the blocking pool yet, skips the processing.
+#[derive(Clone)]
+pub struct AsyncHashEngine {
+    engine: Arc<HashEngine>,
+    permits: Arc<Semaphore>,
+    offload_in_memory: bool,
+}
+
+impl AsyncHashEngine {
+    /// Wraps the engine, allowing as many concurrent detections on the blocking pool as
+    /// the engine has file handles, see `HashEngine::concurrency`.
+    pub fn new(engine: Arc<HashEngine>) -> Self {
+        let concurrency = usize::from(engine.concurrency());
+        Self {
+            engine,
+            permits: Arc::new(Semaphore::new(concurrency)),
+            offload_in_memory: false,
+        }
+    }
+
+    /// Sets the maximum number of detections running on the blocking pool at the same
+    /// time, at least 1. Above `HashEngine::concurrency` detections fail when all file
+    /// handles are in use.
+    pub fn max_concurrency(mut self, concurrency: usize) -> Self {
+        self.permits = Arc::new(Semaphore::new(concurrency.max(1)));
+        self
+    }
+
+    /// Processes engines that hold the whole data set in memory on the blocking pool as
+    /// well, e.g. when a detection is too expensive for the async workers. Defaults to
+    /// processing them inline.
+    pub fn offload_in_memory(mut self, offload: bool) -> Self {
+        self.offload_in_memory = offload;
+        self
+    }
+
+    /// The wrapped engine.
+    pub fn engine(&self) -> &Arc<HashEngine> {
+        &self.engine
+    }
+
+    /// Processes the evidence and copies all selected properties into a `DeviceInfo`.
+    pub async fn process(&self, evidence: Evidence) -> Result<DeviceInfo, AsyncProcessError> {
+        self.process_with(evidence, |results| {
+            results.to_owned_result(results.selected_properties())
+        })
+        .await
+    }
+
+    /// Processes the evidence and reads the common properties into a `Device`.
+    pub async fn device(&self, evidence: Evidence) -> Result<Device, AsyncProcessError> {
+        self.process_with(evidence, Device::from_results).await
+    }
+
+    /// Processes the evidence and reads the owned values needed from the results with `read`.
+    pub async fn process_with<T, F>(
+        &self,
+        evidence: Evidence,
+        read: F,
+    ) -> Result<T, AsyncProcessError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut ResultsHash<'_>) -> T + Send + 'static,
+    {
+        if self.engine.is_in_memory() && !self.offload_in_memory {
+            return process(&self.engine, &evidence, read);
+        }
+
+        let permit = Arc::clone(&self.permits)
+            .acquire_owned()
+            .await
+            .expect("the semaphore is never closed");
+        let engine = Arc::clone(&self.engine);
+        let cancel = CancelOnDrop(Arc::new(AtomicBool::new(false)));
+        let cancelled = Arc::clone(&cancel.0);
+
+        task::spawn_blocking(move || {
+            // the permit is held until the engine is done, even if the caller is gone
+            let _permit = permit;
+            if cancelled.load(Ordering::Acquire) {
+                return Err(AsyncProcessError::Cancelled);
+            }
+            process(&engine, &evidence, read)
+        })
+        .await
+        .map_err(AsyncProcessError::Task)?
+    }
+}
+
+fn process<T>(
+    engine: &HashEngine,
+    evidence: &Evidence,
+    read: impl FnOnce(&mut ResultsHash<'_>) -> T,
+) -> Result<T, AsyncProcessError> {
+    let mut results = engine
+        .process(evidence)
+        .map_err(AsyncProcessError::Process)?;
+    Ok(read(&mut results))
+}
+
+/// Marks the work of a call as cancelled when its future is dropped.
+struct CancelOnDrop(Arc<AtomicBool>);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Release);
+    }
+}
+
+/// The error of the `AsyncHashEngine` calls.
+#[derive(Debug)]
+pub enum AsyncProcessError {
+    /// The engine failed to process the evidence.
+    Process(HashManagerError),
+    /// The caller dropped the future before the processing started.
+    Cancelled,
+    /// The blocking task panicked or the runtime is shutting down.
+    Task(JoinError),
+}
+
+impl Display for AsyncProcessError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AsyncProcessError::Process(_) => write!(f, "error processing the evidence."),
+            AsyncProcessError::Cancelled => write!(f, "the processing was cancelled."),
+            AsyncProcessError::Task(_) => write!(f, "the processing task failed."),
+        }
+    }
+}
+
+impl Error for AsyncProcessError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AsyncProcessError::Process(e) => Some(e),
+            AsyncProcessError::Cancelled => None,
+            AsyncProcessError::Task(e) => Some(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{hash_engine::HashConfig, test_support};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn async_processing() {
+        let in_memory =
+            AsyncHashEngine::new(Arc::new(test_support::engine(HashConfig::HighPerformance)));
+        assert!(in_memory.engine().is_in_memory());
+        let info = in_memory.process(test_support::iphone()).await.unwrap();
+        assert_eq!(info.get_str("PlatformName"), Some("iOS"));
+
+        let low_memory =
+            AsyncHashEngine::new(Arc::new(test_support::engine(HashConfig::LowMemory)))
+                .max_concurrency(1);
+        assert!(!low_memory.engine().is_in_memory());
+        let (first, second) = tokio::join!(
+            low_memory.device(test_support::iphone()),
+            low_memory.process_with(test_support::iphone(), |results| {
+                results.get_bool("IsMobile")
+            }),
+        );
+        assert_eq!(first.unwrap().platform_name.as_deref(), Some("iOS"));
+        assert_eq!(second.unwrap(), Ok(true));
+    }
+
+    #[tokio::test]
+    async fn permits() {
+        use std::{future::Future, sync::mpsc, task::Poll};
+
+        let engine = Arc::new(test_support::engine(HashConfig::LowMemory));
+        let async_engine = AsyncHashEngine::new(Arc::clone(&engine));
+        assert_eq!(
+            async_engine.permits.available_permits(),
+            usize::from(engine.concurrency())
+        );
+
+        // the first call holds the only permit until it is released
+        let async_engine = async_engine.max_concurrency(1);
+        let (started_tx, started_rx) = tokio::sync::oneshot::channel();
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+        let first = tokio::spawn({
+            let async_engine = async_engine.clone();
+            async move {
+                async_engine
+                    .process_with(test_support::iphone(), move |results| {
+                        started_tx.send(()).unwrap();
+                        release_rx.recv().unwrap();
+                        results.get_bool("IsMobile")
+                    })
+                    .await
+            }
+        });
+        started_rx.await.unwrap();
+        assert_eq!(async_engine.permits.available_permits(), 0);
+
+        // the second call waits for the permit, dropping it skips the processing
+        let processed = Arc::new(AtomicBool::new(false));
+        let mut second = Box::pin(async_engine.process_with(test_support::iphone(), {
+            let processed = Arc::clone(&processed);
+            move |_| processed.store(true, Ordering::Release)
+        }));
+        let pending = std::future::poll_fn(|cx| Poll::Ready(second.as_mut().poll(cx).is_pending()));
+        assert!(pending.await);
+        drop(second);
+
+        release_tx.send(()).unwrap();
+        assert_eq!(first.await.unwrap().unwrap(), Ok(true));
+        assert!(!processed.load(Ordering::Acquire));
+        assert_eq!(async_engine.permits.available_permits(), 1);
+    }
+}
@@ -0,0 +1,506 @@
+use std::{
+    ffi::{CStr, CString},
+    fmt::Display,
+    marker::PhantomData,
+    path::PathBuf,
+    slice,
+};
+
+use crate::{
+    fiftyone_degrees::{self, ResourceManager},
+    values::{NoValueReason, ValueType, Version},
+};
+
+/// A date as stored in the header of the data set.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    pub year: i16,
+    pub month: u8,
+    pub day: u8,
+}
+
+impl Display for Date {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl From<fiftyone_degrees::fiftyoneDegreesDate> for Date {
+    fn from(date: fiftyone_degrees::fiftyoneDegreesDate) -> Self {
+        Self {
+            year: date.year,
+            month: date.month,
+            day: date.day,
+        }
+    }
+}
+
+/// Information about the data set currently used by the engine.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DataSetInfo {
+    /// The date the data set was published.
+    pub published: Date,
+    /// The date the next data set is expected to be published.
+    pub next_update: Date,
+    /// The name of the data set, identifying the data tier, e.g. `Lite`.
+    pub name: String,
+    /// The format of the data file, e.g. `HashV41`.
+    pub format: String,
+    /// The version of the data file format.
+    pub format_version: Version,
+    /// The data file the data set was loaded from, None if it was loaded from memory.
+    pub file_path: Option<PathBuf>,
+    pub property_count: u32,
+    pub component_count: u32,
+    pub profile_count: u32,
+}
+
+impl DataSetInfo {
+    /// # Safety
+    /// The data set has to be valid for the duration of the call.
+    pub(crate) unsafe fn read(dataset: *mut fiftyone_degrees::fiftyoneDegreesDataSetHash) -> Self {
+        unsafe {
+            let header = &(*dataset).header;
+            let file_path = CStr::from_ptr((*dataset).b.b.masterFileName.as_ptr());
+
+            Self {
+                published: header.published.into(),
+                next_update: header.nextUpdate.into(),
+                name: string(dataset, header.nameOffset as u32).unwrap_or_default(),
+                format: string(dataset, header.formatOffset as u32).unwrap_or_default(),
+                format_version: Version {
+                    major: header.versionMajor as u32,
+                    minor: Some(header.versionMinor as u32),
+                    patch: Some(header.versionBuild as u32),
+                    build: Some(header.versionRevision as u32),
+                },
+                file_path: (!file_path.is_empty())
+                    .then(|| PathBuf::from(file_path.to_string_lossy().into_owned())),
+                property_count: header.properties.count,
+                component_count: header.components.count,
+                profile_count: header.profiles.count,
+            }
+        }
+    }
+}
+
+/// Metadata of a property in the data set.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PropertyInfo {
+    pub name: String,
+    pub description: String,
+    pub category: String,
+    /// The name of the component the property belongs to, e.g. `HardwarePlatform`.
+    pub component: String,
+    pub value_type: ValueType,
+    /// A link to further information about the property.
+    pub url: String,
+    /// Whether the property can have more than one value.
+    pub is_list: bool,
+    /// Whether the data tier of the loaded data set provides values for the property.
+    pub available: bool,
+    /// Whether the property is part of the results, see `HashEngineBuilder::set_properties`.
+    pub selected: bool,
+}
+
+impl PropertyInfo {
+    /// Reads the metadata of all properties in the data set.
+    ///
+    /// # Safety
+    /// The data set has to be valid for the duration of the call.
+    pub(crate) unsafe fn read_all(
+        dataset: *mut fiftyone_degrees::fiftyoneDegreesDataSetHash,
+    ) -> Vec<Self> {
+        unsafe {
+            let tier = string(dataset, (*dataset).header.nameOffset as u32).unwrap_or_default();
+            let available = (*dataset).b.b.available;
+            let selected: Vec<u32> = if available.is_null() || (*available).items.is_null() {
+                Vec::default()
+            } else {
+                slice::from_raw_parts((*available).items, (*available).count as usize)
+                    .iter()
+                    .map(|property| property.propertyIndex)
+                    .collect()
+            };
+
+            let mut properties = Vec::default();
+            for index in 0..(*(*dataset).properties).count {
+                let mut item = fiftyone_degrees::fiftyoneDegreesCollectionItem::default();
+                let mut exception = fiftyone_degrees::Exception::default();
+                let property = fiftyone_degrees::fiftyoneDegreesPropertyGet(
+                    (*dataset).properties,
+                    index,
+                    &mut item,
+                    &mut exception,
+                );
+                if property.is_null() || !exception.is_ok() {
+                    item.release();
+                    continue;
+                }
+
+                let property = &*property;
+                let tiers = map_names(dataset, property.firstMapIndex, property.mapCount);
+                properties.push(PropertyInfo {
+                    name: string(dataset, property.nameOffset).unwrap_or_default(),
+                    description: string(dataset, property.descriptionOffset).unwrap_or_default(),
+                    category: string(dataset, property.categoryOffset).unwrap_or_default(),
+                    component: component_name(dataset, property.componentIndex as usize)
+                        .unwrap_or_default(),
+                    value_type: ValueType::from_raw(property.valueType),
+                    url: string(dataset, property.urlOffset).unwrap_or_default(),
+                    is_list: property.isList,
+                    available: tiers.is_empty() || tiers.contains(&tier),
+                    selected: selected.contains(&index),
+                });
+                item.release();
+            }
+
+            properties
+        }
+    }
+}
+
+/// Reads the names of the data tiers a property is available in.
+unsafe fn map_names(
+    dataset: *mut fiftyone_degrees::fiftyoneDegreesDataSetHash,
+    first_index: u32,
+    count: u32,
+) -> Vec<String> {
+    unsafe {
+        let maps = (*dataset).maps;
+        let Some(get) = (*maps).get else {
+            return Vec::default();
+        };
+
+        let mut names = Vec::default();
+        for index in first_index..first_index.saturating_add(count) {
+            let mut item = fiftyone_degrees::fiftyoneDegreesCollectionItem::default();
+            let mut exception = fiftyone_degrees::Exception::default();
+            let map = get(maps, index, &mut item, &mut exception)
+                .cast::<fiftyone_degrees::fiftyoneDegreesMap>();
+            if !map.is_null() && exception.is_ok() {
+                names.extend(string(dataset, (*map).nameOffset));
+            }
+            item.release();
+        }
+        names
+    }
+}
+
+/// Reads the name of the component with the given index.
+unsafe fn component_name(
+    dataset: *mut fiftyone_degrees::fiftyoneDegreesDataSetHash,
+    index: usize,
+) -> Option<String> {
+    unsafe {
+        let components = &(*dataset).componentsList;
+        if components.items.is_null() || index >= components.count as usize {
+            return None;
+        }
+
+        let component = (*components.items.add(index))
+            .data
+            .ptr
+            .cast::<fiftyone_degrees::fiftyoneDegreesComponent>();
+        if component.is_null() {
+            return None;
+        }
+        string(dataset, (*component).nameOffset as u32)
+    }
+}
+
+/// A reference to the active data set of a resource manager.
+/// The data set stays valid, even when the engine reloads its data, until the reference
+/// is dropped.
+pub(crate) struct DataSetRef<'a> {
+    dataset: *mut fiftyone_degrees::fiftyoneDegreesDataSetHash,
+    manager: PhantomData<&'a ResourceManager>,
+}
+
+impl DataSetRef<'_> {
+    /// # Safety
+    /// The manager has to be initialized with a hash data set and outlive the reference.
+    pub(crate) unsafe fn acquire(manager: *mut ResourceManager) -> Self {
+        let dataset = unsafe { fiftyone_degrees::fiftyoneDegreesDataSetGet(manager) };
+        Self {
+            dataset: dataset.cast(),
+            manager: PhantomData,
+        }
+    }
+
+    pub(crate) fn as_ptr(&self) -> *mut fiftyone_degrees::fiftyoneDegreesDataSetHash {
+        self.dataset
+    }
+}
+
+impl Drop for DataSetRef<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            fiftyone_degrees::fiftyoneDegreesDataSetRelease(self.dataset.cast());
+        }
+    }
+}
+
+/// Reads a string from the strings collection of the data set.
+///
+/// # Safety
+/// The data set has to be valid for the duration of the call.
+pub(crate) unsafe fn string(
+    dataset: *mut fiftyone_degrees::fiftyoneDegreesDataSetHash,
+    offset: u32,
+) -> Option<String> {
+    unsafe {
+        let mut item = fiftyone_degrees::fiftyoneDegreesCollectionItem::default();
+        let mut exception = fiftyone_degrees::Exception::default();
+        let value = fiftyone_degrees::fiftyoneDegreesStringGet(
+            (*dataset).strings,
+            offset,
+            &mut item,
+            &mut exception,
+        );
+
+        let result = if value.is_null() || !exception.is_ok() {
+            None
+        } else {
+            Some(
+                CStr::from_ptr(std::ptr::from_ref(&(*value).value))
+                    .to_string_lossy()
+                    .into_owned(),
+            )
+        };
+        item.release();
+
+        result
+    }
+}
+
+/// Looks up the index of a property in the required properties of the data set.
+/// In case the property is not part of the required properties, the reason is returned.
+///
+/// # Safety
+/// The data set has to be valid for the duration of the call.
+pub(crate) unsafe fn required_property_index(
+    dataset: *mut fiftyone_degrees::fiftyoneDegreesDataSetHash,
+    name: &str,
+) -> Result<i32, NoValueReason> {
+    let c_name = CString::new(name).map_err(|_| NoValueReason::UnknownProperty)?;
+    let index = unsafe {
+        fiftyone_degrees::fiftyoneDegreesPropertiesGetRequiredPropertyIndexFromName(
+            (*dataset).b.b.available,
+            c_name.as_ptr(),
+        )
+    };
+
+    if index >= 0 {
+        Ok(index)
+    } else if unsafe { property_exists(dataset, &c_name) } {
+        Err(NoValueReason::PropertyNotSelected)
+    } else {
+        Err(NoValueReason::UnknownProperty)
+    }
+}
+
+/// Returns the name of the required property with the given index.
+///
+/// # Safety
+/// The data set has to outlive the returned reference.
+pub(crate) unsafe fn required_property_name<'a>(
+    dataset: *mut fiftyone_degrees::fiftyoneDegreesDataSetHash,
+    index: i32,
+) -> Option<&'a CStr> {
+    unsafe {
+        let available = (*dataset).b.b.available;
+        if index < 0 || available.is_null() || index as u32 >= (*available).count {
+            return None;
+        }
+
+        let name =
+            fiftyone_degrees::fiftyoneDegreesPropertiesGetNameFromRequiredIndex(available, index);
+        if name.is_null() {
+            return None;
+        }
+        Some(CStr::from_ptr(std::ptr::from_ref(&(*name).value)))
+    }
+}
+
+/// Checks if the data set contains a property with the given name,
+/// regardless of the properties selected for the results.
+///
+/// # Safety
+/// The data set has to be valid for the duration of the call.
+pub(crate) unsafe fn property_exists(
+    dataset: *mut fiftyone_degrees::fiftyoneDegreesDataSetHash,
+    name: &CStr,
+) -> bool {
+    unsafe {
+        let mut item = fiftyone_degrees::fiftyoneDegreesCollectionItem::default();
+        let mut exception = fiftyone_degrees::Exception::default();
+        let property = fiftyone_degrees::fiftyoneDegreesPropertyGetByName(
+            (*dataset).properties,
+            (*dataset).strings,
+            name.as_ptr(),
+            &mut item,
+            &mut exception,
+        );
+        let exists = !property.is_null() && exception.is_ok();
+        item.release();
+
+        exists
+    }
+}
+
+/// Looks up the declared value type of a required property in the data set.
+/// Returns None if the property is not part of the required properties.
+///
+/// # Safety
+/// The data set has to be valid for the duration of the call,
+/// e.g. by holding a reference through a results instance.
+pub(crate) unsafe fn property_value_type(
+    dataset: *mut fiftyone_degrees::fiftyoneDegreesDataSetHash,
+    required_index: i32,
+) -> Option<ValueType> {
+    unsafe {
+        required_property(dataset, required_index, |property| {
+            ValueType::from_raw(property.valueType)
+        })
+    }
+}
+
+/// Checks if a required property can have more than one value.
+/// Returns None if the property is not part of the required properties.
+///
+/// # Safety
+/// The data set has to be valid for the duration of the call,
+/// e.g. by holding a reference through a results instance.
+pub(crate) unsafe fn property_is_list(
+    dataset: *mut fiftyone_degrees::fiftyoneDegreesDataSetHash,
+    required_index: i32,
+) -> Option<bool> {
+    unsafe { required_property(dataset, required_index, |property| property.isList) }
+}
+
+/// Reads from the metadata of a required property in the data set.
+unsafe fn required_property<T>(
+    dataset: *mut fiftyone_degrees::fiftyoneDegreesDataSetHash,
+    required_index: i32,
+    read: impl FnOnce(&fiftyone_degrees::fiftyoneDegreesProperty) -> T,
+) -> Option<T> {
+    if required_index < 0 {
+        return None;
+    }
+
+    unsafe {
+        let index = fiftyone_degrees::fiftyoneDegreesPropertiesGetPropertyIndexFromRequiredIndex(
+            (*dataset).b.b.available,
+            required_index,
+        );
+        if index < 0 {
+            return None;
+        }
+
+        let mut item = fiftyone_degrees::fiftyoneDegreesCollectionItem::default();
+        let mut exception = fiftyone_degrees::Exception::default();
+        let property = fiftyone_degrees::fiftyoneDegreesPropertyGet(
+            (*dataset).properties,
+            index as u32,
+            &mut item,
+            &mut exception,
+        );
+
+        let value = if property.is_null() || !exception.is_ok() {
+            None
+        } else {
+            Some(read(&*property))
+        };
+        item.release();
+
+        value
+    }
+}
+
+/// Returns the name of the unique evidence header with the given index.
+///
+/// # Safety
+/// The data set has to be valid for the duration of the call.
+pub(crate) unsafe fn unique_header_name(
+    dataset: *mut fiftyone_degrees::fiftyoneDegreesDataSetHash,
+    index: usize,
+) -> Option<String> {
+    unsafe {
+        let headers = (*dataset).b.b.uniqueHeaders;
+        if headers.is_null() || index >= (*headers).count as usize {
+            return None;
+        }
+
+        let header = &*(*headers).items.add(index);
+        if header.name.is_null() {
+            return None;
+        }
+        Some(CStr::from_ptr(header.name).to_string_lossy().into_owned())
+    }
+}
+
+/// Returns the names of all unique evidence headers the data set uses.
+///
+/// # Safety
+/// The data set has to be valid for the duration of the call.
+pub(crate) unsafe fn unique_header_names(
+    dataset: *mut fiftyone_degrees::fiftyoneDegreesDataSetHash,
+) -> Vec<String> {
+    unsafe {
+        let headers = (*dataset).b.b.uniqueHeaders;
+        if headers.is_null() {
+            return Vec::default();
+        }
+
+        (0..(*headers).count as usize)
+            .filter_map(|index| unique_header_name(dataset, index))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{hash_engine::HashConfig, test_support, values::ValueType};
+
+    #[test]
+    fn dataset_info() {
+        let manager = test_support::engine(HashConfig::InMemory);
+
+        let info = manager.dataset_info();
+        assert_eq!(info.name, "Lite");
+        assert_eq!(info.format_version.major, 4);
+        assert!(info.published < info.next_update);
+        assert_eq!(info.file_path, Some(test_support::data_file()));
+        assert!(info.property_count > 0);
+        assert!(info.component_count > 0);
+        assert!(info.profile_count > 0);
+    }
+
+    #[test]
+    fn properties() {
+        let manager = test_support::builder(HashConfig::InMemory)
+            .set_properties(&["IsMobile"])
+            .init()
+            .unwrap();
+
+        let properties = manager.properties();
+        assert_eq!(
+            properties.len() as u32,
+            manager.dataset_info().property_count
+        );
+
+        let is_mobile = properties.iter().find(|p| p.name == "IsMobile").unwrap();
+        assert_eq!(is_mobile.value_type, ValueType::Boolean);
+        assert_eq!(is_mobile.component, "HardwarePlatform");
+        assert!(is_mobile.available);
+        assert!(is_mobile.selected);
+        assert!(!is_mobile.is_list);
+
+        let platform_name = properties
+            .iter()
+            .find(|p| p.name == "PlatformName")
+            .unwrap();
+        assert!(!platform_name.selected);
+    }
+}
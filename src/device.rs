@@ -0,0 +1,228 @@
+use std::fmt::Display;
+
+use crate::{
+    hash_engine::ResultsHash,
+    property::PropertyKey,
+    values::{ValueType, Version},
+};
+
+macro_rules! device_properties {
+    ($($(#[$doc:meta])* $variant:ident: $value_type:ident $(, $list:ident)?;)*) => {
+        /// The standard properties of the 51Degrees data sets with the value type they are
+        /// expected to have. Not every data tier contains all of them, see
+        /// `HashEngine::properties` for the properties of the loaded data set.
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        pub enum DeviceProperty {
+            $($(#[$doc])* $variant,)*
+        }
+
+        impl DeviceProperty {
+            /// All standard properties.
+            pub const ALL: &[DeviceProperty] = &[$(Self::$variant,)*];
+
+            /// The name of the property in the data set.
+            pub const fn name(self) -> &'static str {
+                match self {
+                    $(Self::$variant => stringify!($variant),)*
+                }
+            }
+
+            /// The value type the property is expected to have.
+            pub const fn value_type(self) -> ValueType {
+                match self {
+                    $(Self::$variant => ValueType::$value_type,)*
+                }
+            }
+
+            /// Whether the property can have more than one value.
+            pub const fn is_list(self) -> bool {
+                match self {
+                    $(Self::$variant => device_properties!(@list $($list)?),)*
+                }
+            }
+        }
+    };
+    (@list list) => { true };
+    (@list) => { false };
+}
+
+device_properties! {
+    // device
+    /// The device is a mobile device, like a smartphone or tablet.
+    IsMobile: Boolean;
+    IsTablet: Boolean;
+    IsSmartPhone: Boolean;
+    IsSmartWatch: Boolean;
+    IsConsole: Boolean;
+    IsTv: Boolean;
+    IsEReader: Boolean;
+    IsMediaHub: Boolean;
+    IsSmallScreen: Boolean;
+    HasTouchScreen: Boolean;
+    /// The type of the device, e.g. `SmartPhone`, `Tablet` or `Desktop`.
+    DeviceType: String;
+    HardwareVendor: String;
+    HardwareModel: String;
+    /// The common names of the device, a device can be sold under several names.
+    HardwareName: String, list;
+    HardwareFamily: String;
+    ScreenPixelsWidth: Integer;
+    ScreenPixelsHeight: Integer;
+    ScreenInchesWidth: Double;
+    ScreenInchesHeight: Double;
+    ScreenInchesDiagonal: Double;
+    PixelRatio: Double;
+    // platform
+    PlatformName: String;
+    PlatformVersion: String;
+    PlatformVendor: String;
+    // browser
+    BrowserName: String;
+    BrowserVersion: String;
+    BrowserVendor: String;
+    // crawler
+    IsCrawler: Boolean;
+    CrawlerName: String;
+    // javascript
+    /// JavaScript that gathers the high entropy client hints of the browser.
+    JavascriptGetHighEntropyValues: JavaScript;
+}
+
+impl PropertyKey for DeviceProperty {
+    fn property_name(&self) -> &str {
+        self.name()
+    }
+}
+
+impl AsRef<str> for DeviceProperty {
+    fn as_ref(&self) -> &str {
+        self.name()
+    }
+}
+
+impl Display for DeviceProperty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The common properties of a detected device, filled from the results in one call.
+/// Properties the results have no value for, e.g. because the data tier does not
+/// contain them or they were not selected, are `None`.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Device {
+    pub device_id: Option<String>,
+    pub is_mobile: Option<bool>,
+    pub is_tablet: Option<bool>,
+    pub is_smart_phone: Option<bool>,
+    pub is_crawler: Option<bool>,
+    pub device_type: Option<String>,
+    pub hardware_vendor: Option<String>,
+    pub hardware_model: Option<String>,
+    pub hardware_name: Vec<String>,
+    pub screen_pixels_width: Option<i32>,
+    pub screen_pixels_height: Option<i32>,
+    pub platform_name: Option<String>,
+    pub platform_version: Option<Version>,
+    pub browser_name: Option<String>,
+    pub browser_version: Option<Version>,
+}
+
+impl Device {
+    /// The properties read by `Device::from_results`, to be passed to
+    /// `HashEngineBuilder::set_properties`.
+    pub const PROPERTIES: &[DeviceProperty] = &[
+        DeviceProperty::IsMobile,
+        DeviceProperty::IsTablet,
+        DeviceProperty::IsSmartPhone,
+        DeviceProperty::IsCrawler,
+        DeviceProperty::DeviceType,
+        DeviceProperty::HardwareVendor,
+        DeviceProperty::HardwareModel,
+        DeviceProperty::HardwareName,
+        DeviceProperty::ScreenPixelsWidth,
+        DeviceProperty::ScreenPixelsHeight,
+        DeviceProperty::PlatformName,
+        DeviceProperty::PlatformVersion,
+        DeviceProperty::BrowserName,
+        DeviceProperty::BrowserVersion,
+    ];
+
+    /// Reads the common properties of the device from the results.
+    pub fn from_results(results: &mut ResultsHash<'_>) -> Self {
+        let mut string = |property: DeviceProperty| -> Option<String> {
+            results.get_str(property).ok().map(str::to_string)
+        };
+
+        Self {
+            device_type: string(DeviceProperty::DeviceType),
+            hardware_vendor: string(DeviceProperty::HardwareVendor),
+            hardware_model: string(DeviceProperty::HardwareModel),
+            platform_name: string(DeviceProperty::PlatformName),
+            browser_name: string(DeviceProperty::BrowserName),
+            device_id: results.get_device_id(),
+            is_mobile: results.get_bool(DeviceProperty::IsMobile).ok(),
+            is_tablet: results.get_bool(DeviceProperty::IsTablet).ok(),
+            is_smart_phone: results.get_bool(DeviceProperty::IsSmartPhone).ok(),
+            is_crawler: results.get_bool(DeviceProperty::IsCrawler).ok(),
+            hardware_name: results
+                .get_values(DeviceProperty::HardwareName)
+                .map(|names| names.map(str::to_string).collect())
+                .unwrap_or_default(),
+            screen_pixels_width: results.get_i32(DeviceProperty::ScreenPixelsWidth).ok(),
+            screen_pixels_height: results.get_i32(DeviceProperty::ScreenPixelsHeight).ok(),
+            platform_version: results.get_version(DeviceProperty::PlatformVersion).ok(),
+            browser_version: results.get_version(DeviceProperty::BrowserVersion).ok(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        device_info::PropertyValue,
+        hash_engine::HashConfig,
+        test_support,
+        values::{NoValueReason, ValueError},
+    };
+
+    use super::*;
+
+    #[test]
+    fn device_properties() {
+        assert_eq!(DeviceProperty::IsMobile.name(), "IsMobile");
+        assert_eq!(DeviceProperty::IsMobile.value_type(), ValueType::Boolean);
+        assert!(DeviceProperty::HardwareName.is_list());
+        assert!(!DeviceProperty::PlatformName.is_list());
+        assert!(DeviceProperty::ALL.contains(&DeviceProperty::BrowserVersion));
+    }
+
+    #[test]
+    fn device_from_results() {
+        let manager = test_support::builder(HashConfig::HighPerformance)
+            .set_properties(Device::PROPERTIES)
+            .init()
+            .unwrap();
+
+        let evidence = test_support::iphone();
+        let mut results = manager.process(&evidence).unwrap();
+        let device = Device::from_results(&mut results);
+
+        assert_eq!(device.is_mobile, Some(true));
+        assert_eq!(device.platform_name.as_deref(), Some("iOS"));
+        assert_eq!(device.device_id, results.get_device_id());
+
+        assert_eq!(
+            results.get_property(DeviceProperty::IsMobile),
+            Ok(PropertyValue::Boolean(true))
+        );
+        assert!(matches!(
+            results.get_property(DeviceProperty::CrawlerName),
+            Err(ValueError::NoValue(
+                NoValueReason::PropertyNotSelected | NoValueReason::UnknownProperty
+            ))
+        ));
+    }
+}
@@ -0,0 +1,151 @@
+use std::{collections::BTreeMap, fmt::Display};
+
+use crate::{
+    metrics::Metrics,
+    property::PropertyKey,
+    values::{self, NoValueReason, ValueType},
+};
+
+/// A value of a property, converted to the value type the data set declares for it.
+/// Values that do not parse as their declared type, like `Unknown` for an integer
+/// property, are kept as `String`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PropertyValue {
+    String(String),
+    Integer(i32),
+    Double(f64),
+    Boolean(bool),
+    JavaScript(String),
+    /// All values of a list property, like `HardwareName`.
+    List(Vec<String>),
+}
+
+impl PropertyValue {
+    pub(crate) fn from_values(value_type: ValueType, is_list: bool, raw: Vec<&str>) -> Self {
+        if is_list {
+            return Self::List(raw.into_iter().map(str::to_string).collect());
+        }
+
+        let value = raw.first().copied().unwrap_or_default();
+        let typed = match value_type {
+            ValueType::Boolean => values::parse_bool(value).map(Self::Boolean),
+            ValueType::Integer | ValueType::SingleByte => {
+                values::parse(value, value_type).map(Self::Integer)
+            }
+            ValueType::Double | ValueType::SinglePrecisionFloat => {
+                values::parse(value, value_type).map(Self::Double)
+            }
+            ValueType::JavaScript => Ok(Self::JavaScript(value.to_string())),
+            ValueType::String | ValueType::Other(_) => Ok(Self::String(value.to_string())),
+        };
+        typed.unwrap_or_else(|_| Self::String(value.to_string()))
+    }
+}
+
+/// Formats the value the way the data set stores it, list values are joined by `,`.
+impl Display for PropertyValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PropertyValue::String(value) | PropertyValue::JavaScript(value) => f.write_str(value),
+            PropertyValue::Integer(value) => write!(f, "{}", value),
+            PropertyValue::Double(value) => write!(f, "{}", value),
+            PropertyValue::Boolean(true) => f.write_str("True"),
+            PropertyValue::Boolean(false) => f.write_str("False"),
+            PropertyValue::List(values) => f.write_str(&values.join(",")),
+        }
+    }
+}
+
+/// An owned copy of a detection, created with `ResultsHash::to_owned_result`.
+/// Unlike the results it does not borrow the engine, so it can be cached, sent to another
+/// thread or kept across reloads of the data set.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DeviceInfo {
+    /// The 51 Degrees device ID of the detection.
+    pub device_id: Option<String>,
+    pub metrics: Metrics,
+    pub(crate) values: BTreeMap<String, Result<PropertyValue, NoValueReason>>,
+}
+
+impl DeviceInfo {
+    /// Returns the value of a property that was copied from the results.
+    /// Properties that were not requested from the results are reported as `UnknownProperty`.
+    pub fn get<P: PropertyKey>(&self, property: P) -> Result<&PropertyValue, NoValueReason> {
+        match self.values.get(property.property_name()) {
+            Some(value) => value.as_ref().map_err(|reason| *reason),
+            None => Err(NoValueReason::UnknownProperty),
+        }
+    }
+
+    /// Returns the value of a string property.
+    pub fn get_str<P: PropertyKey>(&self, property: P) -> Option<&str> {
+        match self.get(property).ok()? {
+            PropertyValue::String(value) | PropertyValue::JavaScript(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a boolean property.
+    pub fn get_bool<P: PropertyKey>(&self, property: P) -> Option<bool> {
+        match self.get(property).ok()? {
+            PropertyValue::Boolean(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of an integer property.
+    pub fn get_i32<P: PropertyKey>(&self, property: P) -> Option<i32> {
+        match self.get(property).ok()? {
+            PropertyValue::Integer(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a floating point property, integer properties are converted as well.
+    pub fn get_f64<P: PropertyKey>(&self, property: P) -> Option<f64> {
+        match self.get(property).ok()? {
+            PropertyValue::Double(value) => Some(*value),
+            PropertyValue::Integer(value) => Some(f64::from(*value)),
+            _ => None,
+        }
+    }
+
+    /// Iterates over all copied properties in the order of their names, with either
+    /// their value or the reason the results had no value for them.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Result<&PropertyValue, NoValueReason>)> {
+        self.values
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_ref().map_err(|reason| *reason)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typed_values() {
+        assert_eq!(
+            PropertyValue::from_values(ValueType::Boolean, false, vec!["True"]),
+            PropertyValue::Boolean(true)
+        );
+        assert_eq!(
+            PropertyValue::from_values(ValueType::Integer, false, vec!["1170"]),
+            PropertyValue::Integer(1170)
+        );
+        assert_eq!(
+            PropertyValue::from_values(ValueType::Integer, false, vec!["Unknown"]),
+            PropertyValue::String("Unknown".to_string())
+        );
+        assert_eq!(
+            PropertyValue::from_values(ValueType::String, true, vec!["iPhone 14", "iPhone 13"]),
+            PropertyValue::List(vec!["iPhone 14".to_string(), "iPhone 13".to_string()])
+        );
+        assert_eq!(
+            PropertyValue::List(vec!["a".to_string(), "b".to_string()]).to_string(),
+            "a,b"
+        );
+    }
+}
@@ -1,10 +1,19 @@
-use std::{ffi::CString, marker::PhantomData};
+use std::{error::Error, ffi::CString, fmt::Display, marker::PhantomData, net::IpAddr};
 
 use crate::fiftyone_degrees::{self, fiftyone_degrees_array_fiftyoneDegreesEvidenceKeyValuePair_t};
 
+#[cfg(feature = "actix-web")]
+mod actix;
+mod client_hints;
+#[cfg(feature = "http")]
+mod request;
+
+pub use client_hints::{Brand, ClientHints, ClientHintsError, HIGH_ENTROPY_VALUES_KEY};
+
 #[derive(Debug)]
 pub struct Evidence {
     data: EvidenceCollection,
+    policy: EvidencePolicy,
 }
 
 #[derive(Debug)]
@@ -21,7 +30,7 @@ struct EvidenceItem {
     value: CString,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum EvidenceKind {
     HeaderString,
     HeaderIPAddresses,
@@ -30,27 +39,151 @@ pub enum EvidenceKind {
     Cookie,
 }
 
+/// How field names and values are checked before they are added to the evidence.
+/// The default rejects NUL characters and does not limit the length of values.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EvidencePolicy {
+    pub nul: NulPolicy,
+    /// Values longer than this many bytes are truncated.
+    pub max_value_len: Option<usize>,
+}
+
+/// What to do with NUL characters, which can not be passed to the engine.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NulPolicy {
+    /// Fail with an `EvidenceError`.
+    #[default]
+    Reject,
+    /// Remove all NUL characters.
+    Strip,
+    /// Cut off everything from the first NUL character on.
+    Truncate,
+}
+
+/// The reasons evidence can not be added.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EvidenceError {
+    /// The field name contains a NUL character at the position.
+    NulInField(usize),
+    /// The value contains a NUL character at the position.
+    NulInValue(usize),
+}
+
+impl Display for EvidenceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EvidenceError::NulInField(position) => {
+                write!(f, "the evidence field contains a NUL character at {}", position)
+            }
+            EvidenceError::NulInValue(position) => {
+                write!(f, "the evidence value contains a NUL character at {}", position)
+            }
+        }
+    }
+}
+
+impl Error for EvidenceError {}
+
+impl EvidencePolicy {
+    fn field(&self, field: &str) -> Result<CString, EvidenceError> {
+        self.c_string(field, EvidenceError::NulInField)
+    }
+
+    fn value(&self, value: &str) -> Result<CString, EvidenceError> {
+        let mut end = self.max_value_len.unwrap_or(value.len()).min(value.len());
+        while !value.is_char_boundary(end) {
+            end -= 1;
+        }
+        self.c_string(&value[..end], EvidenceError::NulInValue)
+    }
+
+    fn c_string(
+        &self,
+        input: &str,
+        error: fn(usize) -> EvidenceError,
+    ) -> Result<CString, EvidenceError> {
+        let sanitized = match (self.nul, input.find('\0')) {
+            (NulPolicy::Strip, Some(_)) => input.replace('\0', ""),
+            (NulPolicy::Truncate, Some(position)) => input[..position].to_string(),
+            _ => input.to_string(),
+        };
+        CString::new(sanitized).map_err(|e| error(e.nul_position()))
+    }
+}
+
 impl Default for Evidence {
     fn default() -> Self {
         Self {
             data: EvidenceCollection::Empty,
+            policy: EvidencePolicy::default(),
         }
     }
 }
 
 impl Evidence {
-    pub fn add<T: AsRef<str>>(mut self, kind: EvidenceKind, field: T, value: T) -> Self {
-        let field: CString = CString::new(field.as_ref()).expect("error creating c string");
-        let value: CString = CString::new(value.as_ref()).expect("error creating c string");
+    /// Adds a piece of evidence, like a header.
+    ///
+    /// # Panics
+    /// If the field or value contain a NUL character and the policy rejects them,
+    /// use `Evidence::try_add` to handle the error instead.
+    #[deprecated(
+        since = "1.0.0-pre",
+        note = "panics on NUL characters, use `Evidence::try_add` instead"
+    )]
+    pub fn add<T: AsRef<str>>(self, kind: EvidenceKind, field: T, value: T) -> Self {
+        self.try_add(kind, field, value).expect("error creating c string")
+    }
+
+    /// Adds a piece of evidence, like a header, checked with the policy of the evidence.
+    pub fn try_add<F: AsRef<str>, V: AsRef<str>>(
+        mut self,
+        kind: EvidenceKind,
+        field: F,
+        value: V,
+    ) -> Result<Self, EvidenceError> {
+        let field = self.policy.field(field.as_ref())?;
+        let value = self.policy.value(value.as_ref())?;
         self.data.add_evidence(kind, field, value);
+        Ok(self)
+    }
+
+    /// Sets the policy that checks the evidence added from now on, evidence that was
+    /// already added is not checked again. Use `Evidence::try_new_with_user_agent_and_policy`
+    /// for a user agent that needs the policy.
+    pub fn with_policy(mut self, policy: EvidencePolicy) -> Self {
+        self.policy = policy;
         self
     }
 
+    /// Creates evidence consisting of only the user agent.
+    ///
+    /// # Panics
+    /// If the user agent contains a NUL character,
+    /// use `Evidence::try_new_with_user_agent` to handle the error instead.
+    #[deprecated(
+        since = "1.0.0-pre",
+        note = "panics on NUL characters, use `Evidence::try_new_with_user_agent` instead"
+    )]
     pub fn new_with_user_agent<T: AsRef<str>>(ua: T) -> Self {
-        let ua: CString = CString::new(ua.as_ref()).expect("error creating c string");
-        Self {
-            data: EvidenceCollection::UserAgentOnly(ua),
-        }
+        Self::try_new_with_user_agent(ua).expect("error creating c string")
+    }
+
+    /// Creates evidence consisting of only the user agent,
+    /// fails if the user agent contains a NUL character.
+    pub fn try_new_with_user_agent<T: AsRef<str>>(ua: T) -> Result<Self, EvidenceError> {
+        Self::try_new_with_user_agent_and_policy(ua, EvidencePolicy::default())
+    }
+
+    /// Creates evidence consisting of only the user agent, checked by the policy.
+    /// The policy also applies to the evidence added later.
+    pub fn try_new_with_user_agent_and_policy<T: AsRef<str>>(
+        ua: T,
+        policy: EvidencePolicy,
+    ) -> Result<Self, EvidenceError> {
+        Ok(Self {
+            data: EvidenceCollection::UserAgentOnly(policy.value(ua.as_ref())?),
+            policy,
+        })
     }
 
     pub fn len(&self) -> usize {
@@ -64,6 +197,160 @@ impl Evidence {
     pub fn is_empty(&self) -> bool {
         matches!(&self.data, EvidenceCollection::Empty)
     }
+
+    /// Adds the parameters of a URL query string like `a=1&b=2` as `EvidenceKind::Query`.
+    /// The parameters are percent decoded, parameters that are not valid UTF-8
+    /// or are rejected by the policy after decoding are skipped.
+    pub fn add_query_string(mut self, query: &str) -> Self {
+        for pair in query.trim_start_matches('?').split('&') {
+            let (field, value) = pair.split_once('=').unwrap_or((pair, ""));
+            if field.is_empty() {
+                continue;
+            }
+            if let (Some(field), Some(value)) = (percent_decode(field), percent_decode(value)) {
+                self.push(EvidenceKind::Query, &field, &value);
+            }
+        }
+        self
+    }
+
+    /// Adds the cookies of a `Cookie` header like `a=1; b=2` as `EvidenceKind::Cookie`.
+    /// Cookies rejected by the policy are skipped.
+    pub fn add_cookies(mut self, cookie_header: &str) -> Self {
+        for cookie in cookie_header.split(';') {
+            let Some((name, value)) = cookie.split_once('=') else {
+                continue;
+            };
+            let name = name.trim();
+            if !name.is_empty() {
+                self.push(EvidenceKind::Cookie, name, value.trim().trim_matches('"'));
+            }
+        }
+        self
+    }
+
+    /// Adds the IP address of the client as the `client-ip` server evidence.
+    pub fn add_client_ip(mut self, ip: IpAddr) -> Self {
+        self.push(EvidenceKind::Server, "client-ip", &ip.to_string());
+        self
+    }
+
+    /// Removes all evidence the engine does not use, see `HashEngine::evidence_keys`.
+    /// Passing less evidence to the engine reduces the work of processing it,
+    /// without changing the results.
+    pub fn retain_keys(mut self, keys: &EvidenceKeys) -> Self {
+        self.data = match self.data {
+            EvidenceCollection::EvidenceKeyValues(mut values) => {
+                values.retain(|item| keys.contains(item.kind, &item.field.to_string_lossy()));
+                if values.is_empty() {
+                    EvidenceCollection::Empty
+                } else {
+                    EvidenceCollection::EvidenceKeyValues(values)
+                }
+            }
+            data => data,
+        };
+        self
+    }
+
+    /// Adds a header of a request as `EvidenceKind::HeaderString`, the `Cookie` header as
+    /// `EvidenceKind::Cookie` with `Evidence::add_cookies`. Values that are not valid UTF-8
+    /// or are rejected by the policy are skipped.
+    #[cfg(any(feature = "http", feature = "actix-web"))]
+    fn add_header(mut self, name: &str, value: &[u8]) -> Self {
+        let Ok(value) = std::str::from_utf8(value) else {
+            return self;
+        };
+
+        if name.eq_ignore_ascii_case("cookie") {
+            self.add_cookies(value)
+        } else {
+            self.push(EvidenceKind::HeaderString, name, value);
+            self
+        }
+    }
+
+    /// Adds the evidence unless the policy rejects the field or value.
+    fn push(&mut self, kind: EvidenceKind, field: &str, value: &str) {
+        if let (Ok(field), Ok(value)) = (self.policy.field(field), self.policy.value(value)) {
+            self.data.add_evidence(kind, field, value);
+        }
+    }
+}
+
+/// The evidence keys used by the hash engine, returned by `HashEngine::evidence_keys`.
+/// The engine reads the headers it uses from the headers or the query string,
+/// evidence with a key starting with `51D_`, like `51D_deviceId`, is read from the
+/// query string and cookies.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EvidenceKeys {
+    headers: Vec<String>,
+}
+
+impl EvidenceKeys {
+    /// The kinds of evidence the engine reads the headers from.
+    pub const HEADER_KINDS: &[EvidenceKind] = &[
+        EvidenceKind::HeaderString,
+        EvidenceKind::HeaderIPAddresses,
+        EvidenceKind::Query,
+    ];
+
+    /// The kinds of evidence the engine reads the `51D_` keys from.
+    pub const SPECIAL_KINDS: &[EvidenceKind] = &[EvidenceKind::Query, EvidenceKind::Cookie];
+
+    pub(crate) fn new(headers: Vec<String>) -> Self {
+        Self {
+            headers: headers
+                .into_iter()
+                .map(|header| header.to_ascii_lowercase())
+                .collect(),
+        }
+    }
+
+    /// The lower case names of the headers the data set uses.
+    pub fn headers(&self) -> &[String] {
+        &self.headers
+    }
+
+    /// Checks if the engine uses evidence of the kind with the field name.
+    pub fn contains(&self, kind: EvidenceKind, field: &str) -> bool {
+        if Self::SPECIAL_KINDS.contains(&kind)
+            && field.get(..4).is_some_and(|prefix| prefix.eq_ignore_ascii_case("51D_"))
+        {
+            return true;
+        }
+
+        Self::HEADER_KINDS.contains(&kind)
+            && self
+                .headers
+                .iter()
+                .any(|header| header.eq_ignore_ascii_case(field))
+    }
+}
+
+/// Decodes `%XX` escapes and `+` as space, returns None for invalid UTF-8.
+fn percent_decode(input: &str) -> Option<String> {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = |byte: u8| (byte as char).to_digit(16);
+                match (hex(bytes[i + 1]), hex(bytes[i + 2])) {
+                    (Some(high), Some(low)) => {
+                        decoded.push((high * 16 + low) as u8);
+                        i += 2;
+                    }
+                    _ => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8(decoded).ok()
 }
 
 impl EvidenceKind {
@@ -121,26 +408,187 @@ impl<'a> EvidenceRef<'a> {
         let kv_array =
             unsafe { fiftyone_degrees::fiftyoneDegreesEvidenceCreate(evidence.len() as u32) };
         assert!(!kv_array.is_null());
-        match &evidence.data {
-            EvidenceCollection::Empty => {}
-            EvidenceCollection::UserAgentOnly(ua) => unsafe {
-                fiftyone_degrees::fiftyoneDegreesEvidenceAddString(
-                    kv_array, 
-                    fiftyone_degrees::e_fiftyone_degrees_evidence_prefix_FIFTYONE_DEGREES_EVIDENCE_HTTP_HEADER_STRING, 
-                    c"user-agent".as_ptr(), 
-                    ua.as_ptr()
-                );
-            },
-            EvidenceCollection::EvidenceKeyValues(kvs) => for item in kvs {
-                unsafe {
-                    fiftyone_degrees::fiftyoneDegreesEvidenceAddString(kv_array, item.kind.to_prefix(), item.field.as_ptr(), item.value.as_ptr());
-                };
-            }
-
-        }
+        unsafe { add_evidence(kv_array, evidence) };
         Self {
             kv_array,
             backing_store: PhantomData,
         }
     }
 }
+
+/// An evidence array of the hash engine that is allocated once and refilled for every
+/// processed evidence, instead of allocating a new array like `EvidenceRef` does.
+/// The array grows if the evidence does not fit.
+#[derive(Debug)]
+pub(crate) struct EvidenceArray {
+    kv_array: *mut fiftyone_degrees_array_fiftyoneDegreesEvidenceKeyValuePair_t,
+    capacity: usize,
+}
+
+// SAFETY: The array is owned exclusively by the wrapper and only points into evidence
+// while it is borrowed by `fill`.
+unsafe impl Send for EvidenceArray {}
+
+impl Drop for EvidenceArray {
+    fn drop(&mut self) {
+        unsafe {
+            fiftyone_degrees::fiftyoneDegreesEvidenceFree(self.kv_array);
+        }
+    }
+}
+
+impl EvidenceArray {
+    /// Allocates an array for up to `capacity` evidence values.
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        let kv_array = unsafe { fiftyone_degrees::fiftyoneDegreesEvidenceCreate(capacity as u32) };
+        assert!(!kv_array.is_null());
+        Self { kv_array, capacity }
+    }
+
+    /// Replaces the content of the array with the evidence and returns the filled array.
+    /// The array points into the evidence, the returned pointer must not be used
+    /// after the evidence is dropped.
+    ///
+    /// The array is cleared by resetting its count, as `fiftyoneDegreesEvidenceAddString`
+    /// overwrites every field of a slot it fills, including the value a previous detection
+    /// parsed into it. The library never reads the slots beyond the count.
+    pub(crate) fn fill(
+        &mut self,
+        evidence: &Evidence,
+    ) -> *mut fiftyone_degrees_array_fiftyoneDegreesEvidenceKeyValuePair_t {
+        if evidence.len() > self.capacity {
+            *self = Self::with_capacity(evidence.len());
+        }
+        unsafe {
+            (*self.kv_array).count = 0;
+            (*self.kv_array).pseudoEvidence = std::ptr::null_mut();
+            add_evidence(self.kv_array, evidence);
+        }
+        self.kv_array
+    }
+}
+
+/// Adds all values of the evidence to the array.
+///
+/// # Safety
+/// The array has to have the capacity for all values of the evidence.
+unsafe fn add_evidence(
+    kv_array: *mut fiftyone_degrees_array_fiftyoneDegreesEvidenceKeyValuePair_t,
+    evidence: &Evidence,
+) {
+    match &evidence.data {
+        EvidenceCollection::Empty => {}
+        EvidenceCollection::UserAgentOnly(ua) => unsafe {
+            fiftyone_degrees::fiftyoneDegreesEvidenceAddString(
+                kv_array, 
+                fiftyone_degrees::e_fiftyone_degrees_evidence_prefix_FIFTYONE_DEGREES_EVIDENCE_HTTP_HEADER_STRING, 
+                c"user-agent".as_ptr(), 
+                ua.as_ptr()
+            );
+        },
+        EvidenceCollection::EvidenceKeyValues(kvs) => for item in kvs {
+            unsafe {
+                fiftyone_degrees::fiftyoneDegreesEvidenceAddString(kv_array, item.kind.to_prefix(), item.field.as_ptr(), item.value.as_ptr());
+            };
+        }
+
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::{CStr, c_char};
+
+    use crate::test_support;
+
+    use super::*;
+
+    #[test]
+    fn refilled_evidence_array() {
+        let first = test_support::evidence(&[
+            (EvidenceKind::HeaderString, "user-agent", "Mozilla/5.0 (iPhone)"),
+            (EvidenceKind::HeaderString, "sec-ch-ua-mobile", "?1"),
+            (EvidenceKind::Query, "51D_deviceId", "12280-0-0-0"),
+        ]);
+        let second = test_support::evidence(&[(EvidenceKind::Cookie, "51D_ProfileIds", "12280")]);
+
+        let mut array = EvidenceArray::with_capacity(1);
+        let kv_array = array.fill(&first);
+        assert_eq!(array.capacity, 3);
+        unsafe {
+            assert_eq!((*kv_array).count, 3);
+            // a detection stores the values it parsed in the slots
+            let dummy = c"parsed";
+            for index in 0..3 {
+                (*(*kv_array).items.add(index)).parsedValue = dummy.as_ptr().cast();
+            }
+        }
+
+        let kv_array = array.fill(&second);
+        let EvidenceCollection::EvidenceKeyValues(items) = &second.data else {
+            panic!("expected key values");
+        };
+        unsafe {
+            assert_eq!((*kv_array).count, 1);
+            assert!((*kv_array).pseudoEvidence.is_null());
+            let slot = &*(*kv_array).items;
+            assert_eq!(slot.prefix, EvidenceKind::Cookie.to_prefix());
+            assert_eq!(CStr::from_ptr(slot.field), c"51D_ProfileIds");
+            assert_eq!(slot.originalValue.cast::<c_char>(), items[0].value.as_ptr());
+            assert!(slot.parsedValue.is_null());
+        }
+    }
+
+    #[test]
+    fn evidence_policy() {
+        assert_eq!(
+            Evidence::try_new_with_user_agent("Mozilla\0/5.0").unwrap_err(),
+            EvidenceError::NulInValue(7)
+        );
+        assert_eq!(
+            Evidence::default()
+                .try_add(EvidenceKind::HeaderString, "user\0agent", "Mozilla/5.0")
+                .unwrap_err(),
+            EvidenceError::NulInField(4)
+        );
+
+        let policy = EvidencePolicy {
+            nul: NulPolicy::Strip,
+            max_value_len: Some(6),
+        };
+        let evidence = Evidence::default()
+            .with_policy(policy)
+            .try_add(EvidenceKind::HeaderString, "user-agent", "Mo\0zilla/5.0")
+            .unwrap();
+        let EvidenceCollection::EvidenceKeyValues(items) = &evidence.data else {
+            panic!("expected key values");
+        };
+        assert_eq!(items[0].value.as_bytes(), b"Mozil");
+
+        let policy = EvidencePolicy {
+            nul: NulPolicy::Truncate,
+            max_value_len: Some(2),
+        };
+        let evidence = Evidence::default()
+            .with_policy(policy)
+            .try_add(EvidenceKind::HeaderString, "accept\0-language", "äb")
+            .unwrap();
+        let EvidenceCollection::EvidenceKeyValues(items) = &evidence.data else {
+            panic!("expected key values");
+        };
+        assert_eq!(items[0].field.as_bytes(), b"accept");
+        assert_eq!(items[0].value.as_bytes(), "ä".as_bytes());
+
+        let policy = EvidencePolicy {
+            nul: NulPolicy::Strip,
+            max_value_len: None,
+        };
+        let evidence =
+            Evidence::try_new_with_user_agent_and_policy("Mo\0zilla/5.0", policy).unwrap();
+        let EvidenceCollection::UserAgentOnly(ua) = &evidence.data else {
+            panic!("expected a user agent");
+        };
+        assert_eq!(ua.as_bytes(), b"Mozilla/5.0");
+        assert_eq!(evidence.policy.nul, NulPolicy::Strip);
+    }
+}
@@ -0,0 +1,25 @@
+use actix_web::HttpRequest;
+
+use super::Evidence;
+
+impl Evidence {
+    /// Creates evidence from an actix-web request, like `Evidence::from_request`.
+    /// The headers are added as `EvidenceKind::HeaderString`, the cookies, the parameters
+    /// of the query string and the address of the peer as in `Evidence::add_cookies`,
+    /// `Evidence::add_query_string` and `Evidence::add_client_ip`.
+    pub fn from_actix_request(request: &HttpRequest) -> Self {
+        let mut evidence = request
+            .headers()
+            .iter()
+            .fold(Evidence::default(), |evidence, (name, value)| {
+                evidence.add_header(name.as_str(), value.as_bytes())
+            });
+        if !request.query_string().is_empty() {
+            evidence = evidence.add_query_string(request.query_string());
+        }
+        if let Some(addr) = request.peer_addr() {
+            evidence = evidence.add_client_ip(addr.ip());
+        }
+        evidence
+    }
+}
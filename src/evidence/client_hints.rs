@@ -0,0 +1,488 @@
+use std::{error::Error, fmt::Display};
+
+use super::{Evidence, EvidenceKind};
+
+/// The evidence key of the base64 encoded JSON returned by
+/// `navigator.userAgentData.getHighEntropyValues()`.
+pub const HIGH_ENTROPY_VALUES_KEY: &str = "51D_gethighentropyvalues";
+
+const UA: &str = "sec-ch-ua";
+const FULL_VERSION_LIST: &str = "sec-ch-ua-full-version-list";
+const MOBILE: &str = "sec-ch-ua-mobile";
+const PLATFORM: &str = "sec-ch-ua-platform";
+const PLATFORM_VERSION: &str = "sec-ch-ua-platform-version";
+const MODEL: &str = "sec-ch-ua-model";
+const ARCH: &str = "sec-ch-ua-arch";
+const BITNESS: &str = "sec-ch-ua-bitness";
+const WOW64: &str = "sec-ch-ua-wow64";
+
+/// A brand of the browser with its version, like `"Chromium";v="98"`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Brand {
+    pub brand: String,
+    pub version: String,
+}
+
+/// The User-Agent Client Hints of a browser, read from the `Sec-CH-UA*` headers or from
+/// the values returned by `navigator.userAgentData.getHighEntropyValues()`.
+/// Hints that were not sent are empty.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ClientHints {
+    /// `Sec-CH-UA`, the brands with their major version.
+    pub brands: Vec<Brand>,
+    /// `Sec-CH-UA-Full-Version-List`, the brands with their full version.
+    pub full_version_list: Vec<Brand>,
+    /// `Sec-CH-UA-Mobile`
+    pub mobile: Option<bool>,
+    /// `Sec-CH-UA-Platform`
+    pub platform: Option<String>,
+    /// `Sec-CH-UA-Platform-Version`
+    pub platform_version: Option<String>,
+    /// `Sec-CH-UA-Model`
+    pub model: Option<String>,
+    /// `Sec-CH-UA-Arch`
+    pub architecture: Option<String>,
+    /// `Sec-CH-UA-Bitness`
+    pub bitness: Option<String>,
+    /// `Sec-CH-UA-WoW64`
+    pub wow64: Option<bool>,
+}
+
+/// The reasons client hints could not be read.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ClientHintsError {
+    /// The value of the header is not a valid structured field of the type the header requires.
+    InvalidHeader { header: String, value: String },
+    /// The GetHighEntropyValues payload is not valid base64 encoded JSON.
+    InvalidPayload(String),
+}
+
+impl Display for ClientHintsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClientHintsError::InvalidHeader { header, value } => {
+                write!(f, "invalid value for the header {}: {}", header, value)
+            }
+            ClientHintsError::InvalidPayload(reason) => {
+                write!(f, "invalid GetHighEntropyValues payload: {}", reason)
+            }
+        }
+    }
+}
+
+impl Error for ClientHintsError {}
+
+impl ClientHints {
+    /// Reads the client hints from HTTP headers, other headers are ignored.
+    /// Header names are compared case insensitive, the values are validated against
+    /// the structured field syntax (RFC 8941) of the respective header.
+    pub fn from_headers<'a, I>(headers: I) -> Result<Self, ClientHintsError>
+    where
+        I: IntoIterator<Item = (&'a str, &'a str)>,
+    {
+        let mut hints = Self::default();
+        for (name, value) in headers {
+            let name = name.to_ascii_lowercase();
+            let invalid = || ClientHintsError::InvalidHeader {
+                header: name.clone(),
+                value: value.to_string(),
+            };
+
+            match name.as_str() {
+                UA => hints.brands = parse_brands(value).ok_or_else(invalid)?,
+                FULL_VERSION_LIST => {
+                    hints.full_version_list = parse_brands(value).ok_or_else(invalid)?
+                }
+                MOBILE => hints.mobile = Some(parse_boolean(value).ok_or_else(invalid)?),
+                WOW64 => hints.wow64 = Some(parse_boolean(value).ok_or_else(invalid)?),
+                PLATFORM => hints.platform = Some(parse_string(value).ok_or_else(invalid)?),
+                PLATFORM_VERSION => {
+                    hints.platform_version = Some(parse_string(value).ok_or_else(invalid)?)
+                }
+                MODEL => hints.model = Some(parse_string(value).ok_or_else(invalid)?),
+                ARCH => hints.architecture = Some(parse_string(value).ok_or_else(invalid)?),
+                BITNESS => hints.bitness = Some(parse_string(value).ok_or_else(invalid)?),
+                _ => {}
+            }
+        }
+        Ok(hints)
+    }
+
+    /// Reads the client hints from the JSON returned by
+    /// `navigator.userAgentData.getHighEntropyValues()`.
+    #[cfg(feature = "serde")]
+    pub fn from_high_entropy_values(json: &str) -> Result<Self, ClientHintsError> {
+        let values: HighEntropyValues = serde_json::from_str(json)
+            .map_err(|e| ClientHintsError::InvalidPayload(e.to_string()))?;
+
+        Ok(Self {
+            brands: values.brands,
+            full_version_list: values.full_version_list,
+            mobile: values.mobile,
+            platform: values.platform,
+            platform_version: values.platform_version,
+            model: values.model,
+            architecture: values.architecture,
+            bitness: values.bitness,
+            wow64: values.wow64,
+        })
+    }
+
+    /// Reads the client hints from the base64 encoded JSON of the
+    /// `51D_gethighentropyvalues` evidence.
+    #[cfg(feature = "serde")]
+    pub fn from_high_entropy_values_base64(payload: &str) -> Result<Self, ClientHintsError> {
+        let json = decode_base64(payload)
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .ok_or_else(|| ClientHintsError::InvalidPayload("invalid base64".to_string()))?;
+        Self::from_high_entropy_values(&json)
+    }
+
+    /// Returns the hints as `Sec-CH-UA*` headers with structured field values.
+    pub fn to_headers(&self) -> Vec<(&'static str, String)> {
+        let mut headers = Vec::default();
+        if !self.brands.is_empty() {
+            headers.push((UA, serialize_brands(&self.brands)));
+        }
+        if !self.full_version_list.is_empty() {
+            headers.push((FULL_VERSION_LIST, serialize_brands(&self.full_version_list)));
+        }
+        let booleans = [(MOBILE, self.mobile), (WOW64, self.wow64)];
+        for (header, value) in booleans {
+            if let Some(value) = value {
+                headers.push((header, serialize_boolean(value).to_string()));
+            }
+        }
+        let strings = [
+            (PLATFORM, &self.platform),
+            (PLATFORM_VERSION, &self.platform_version),
+            (MODEL, &self.model),
+            (ARCH, &self.architecture),
+            (BITNESS, &self.bitness),
+        ];
+        for (header, value) in strings {
+            if let Some(value) = value {
+                headers.push((header, serialize_string(value)));
+            }
+        }
+        headers
+    }
+}
+
+impl Evidence {
+    /// Adds the client hints as `Sec-CH-UA*` headers.
+    pub fn add_client_hints(mut self, hints: &ClientHints) -> Self {
+        for (header, value) in hints.to_headers() {
+            self.push(EvidenceKind::HeaderString, header, &value);
+        }
+        self
+    }
+
+    /// Passes the base64 encoded JSON of `navigator.userAgentData.getHighEntropyValues()`
+    /// to the engine as is, as the `51D_gethighentropyvalues` query evidence.
+    pub fn add_high_entropy_values(mut self, payload: &str) -> Self {
+        self.push(EvidenceKind::Query, HIGH_ENTROPY_VALUES_KEY, payload);
+        self
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HighEntropyValues {
+    #[serde(default)]
+    brands: Vec<Brand>,
+    #[serde(default)]
+    full_version_list: Vec<Brand>,
+    mobile: Option<bool>,
+    platform: Option<String>,
+    platform_version: Option<String>,
+    model: Option<String>,
+    architecture: Option<String>,
+    bitness: Option<String>,
+    wow64: Option<bool>,
+}
+
+/// A parser for the subset of structured field values (RFC 8941) used by client hints.
+struct Parser<'a> {
+    input: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Self {
+        let mut parser = Self {
+            input: input.as_bytes(),
+            position: 0,
+        };
+        parser.skip_whitespace();
+        parser
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.position).copied()
+    }
+
+    fn bump(&mut self) -> Option<u8> {
+        let byte = self.peek()?;
+        self.position += 1;
+        Some(byte)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t')) {
+            self.position += 1;
+        }
+    }
+
+    /// Succeeds if only whitespace is left.
+    fn end(mut self) -> Option<()> {
+        self.skip_whitespace();
+        (self.position == self.input.len()).then_some(())
+    }
+
+    fn string(&mut self) -> Option<String> {
+        if self.bump()? != b'"' {
+            return None;
+        }
+        let mut value = String::default();
+        loop {
+            match self.bump()? {
+                b'"' => return Some(value),
+                b'\\' => match self.bump()? {
+                    byte @ (b'"' | b'\\') => value.push(byte as char),
+                    _ => return None,
+                },
+                byte @ 0x20..=0x7e => value.push(byte as char),
+                _ => return None,
+            }
+        }
+    }
+
+    fn boolean(&mut self) -> Option<bool> {
+        if self.bump()? != b'?' {
+            return None;
+        }
+        match self.bump()? {
+            b'1' => Some(true),
+            b'0' => Some(false),
+            _ => None,
+        }
+    }
+
+    fn key(&mut self) -> Option<String> {
+        let start = self.position;
+        match self.peek()? {
+            b'a'..=b'z' | b'*' => {}
+            _ => return None,
+        }
+        while matches!(
+            self.peek(),
+            Some(b'a'..=b'z' | b'0'..=b'9' | b'_' | b'-' | b'.' | b'*')
+        ) {
+            self.position += 1;
+        }
+        Some(String::from_utf8_lossy(&self.input[start..self.position]).into_owned())
+    }
+
+    /// Parses the parameters of an item, only string and boolean values are supported.
+    fn parameters(&mut self) -> Option<Vec<(String, Option<String>)>> {
+        let mut parameters = Vec::default();
+        while self.peek() == Some(b';') {
+            self.position += 1;
+            self.skip_whitespace();
+            let key = self.key()?;
+            let value = if self.peek() == Some(b'=') {
+                self.position += 1;
+                match self.peek()? {
+                    b'"' => Some(self.string()?),
+                    b'?' => Some(self.boolean()?.to_string()),
+                    _ => return None,
+                }
+            } else {
+                None
+            };
+            parameters.push((key, value));
+        }
+        Some(parameters)
+    }
+}
+
+fn parse_string(value: &str) -> Option<String> {
+    let mut parser = Parser::new(value);
+    let value = parser.string()?;
+    parser.end()?;
+    Some(value)
+}
+
+fn parse_boolean(value: &str) -> Option<bool> {
+    let mut parser = Parser::new(value);
+    let value = parser.boolean()?;
+    parser.end()?;
+    Some(value)
+}
+
+/// Parses a list of brands like `"Chromium";v="98", "Google Chrome";v="98"`.
+fn parse_brands(value: &str) -> Option<Vec<Brand>> {
+    let mut parser = Parser::new(value);
+    let mut brands = Vec::default();
+    if parser.peek().is_none() {
+        return Some(brands);
+    }
+
+    loop {
+        let brand = parser.string()?;
+        let version = parser
+            .parameters()?
+            .into_iter()
+            .find(|(key, _)| key == "v")
+            .and_then(|(_, value)| value)
+            .unwrap_or_default();
+        brands.push(Brand { brand, version });
+
+        parser.skip_whitespace();
+        match parser.bump() {
+            None => return Some(brands),
+            Some(b',') => parser.skip_whitespace(),
+            Some(_) => return None,
+        }
+        // a trailing comma is not allowed
+        parser.peek()?;
+    }
+}
+
+fn serialize_string(value: &str) -> String {
+    let mut serialized = String::with_capacity(value.len() + 2);
+    serialized.push('"');
+    for c in value.chars() {
+        if matches!(c, '"' | '\\') {
+            serialized.push('\\');
+        }
+        serialized.push(c);
+    }
+    serialized.push('"');
+    serialized
+}
+
+fn serialize_boolean(value: bool) -> &'static str {
+    if value { "?1" } else { "?0" }
+}
+
+fn serialize_brands(brands: &[Brand]) -> String {
+    brands
+        .iter()
+        .map(|brand| {
+            format!(
+                "{};v={}",
+                serialize_string(&brand.brand),
+                serialize_string(&brand.version)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Decodes standard and URL safe base64, with or without padding.
+#[cfg(feature = "serde")]
+fn decode_base64(input: &str) -> Option<Vec<u8>> {
+    let mut decoded = Vec::with_capacity(input.len() * 3 / 4);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for byte in input.trim().trim_end_matches('=').bytes() {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            _ => return None,
+        };
+        buffer = (buffer << 6) | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            decoded.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Some(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BRANDS: &str = r#""Not A;Brand";v="99", "Chromium";v="98", "Google Chrome";v="98""#;
+
+    #[test]
+    fn client_hints_from_headers() {
+        let hints = ClientHints::from_headers([
+            ("Sec-CH-UA", BRANDS),
+            ("sec-ch-ua-mobile", "?0"),
+            ("sec-ch-ua-platform", "\"Windows\""),
+            ("sec-ch-ua-platform-version", "\"14.0.0\""),
+            ("user-agent", "Mozilla/5.0"),
+        ])
+        .unwrap();
+
+        assert_eq!(hints.brands.len(), 3);
+        assert_eq!(
+            hints.brands[0],
+            Brand {
+                brand: "Not A;Brand".to_string(),
+                version: "99".to_string()
+            }
+        );
+        assert_eq!(hints.mobile, Some(false));
+        assert_eq!(hints.platform.as_deref(), Some("Windows"));
+        assert_eq!(hints.platform_version.as_deref(), Some("14.0.0"));
+        assert_eq!(hints.model, None);
+
+        let headers = hints.to_headers();
+        let reparsed =
+            ClientHints::from_headers(headers.iter().map(|(name, value)| (*name, value.as_str())))
+                .unwrap();
+        assert_eq!(reparsed, hints);
+        assert_eq!(Evidence::default().add_client_hints(&hints).len(), 4);
+    }
+
+    #[test]
+    fn invalid_structured_fields() {
+        for (header, value) in [
+            ("sec-ch-ua-platform", "Windows"),
+            ("sec-ch-ua-platform", "\"Windows"),
+            ("sec-ch-ua-mobile", "?2"),
+            ("sec-ch-ua-mobile", "true"),
+            ("sec-ch-ua", r#""Chromium";v="98","#),
+            ("sec-ch-ua", r#""Chromium" "Edge""#),
+        ] {
+            assert_eq!(
+                ClientHints::from_headers([(header, value)]),
+                Err(ClientHintsError::InvalidHeader {
+                    header: header.to_string(),
+                    value: value.to_string()
+                })
+            );
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn high_entropy_values() {
+        let json = r#"{"architecture":"x86","bitness":"64","brands":[{"brand":"Chromium","version":"98"}],"fullVersionList":[{"brand":"Chromium","version":"98.0.4758.102"}],"mobile":false,"model":"","platform":"Windows","platformVersion":"14.0.0"}"#;
+        let hints = ClientHints::from_high_entropy_values(json).unwrap();
+        assert_eq!(hints.full_version_list[0].version, "98.0.4758.102");
+        assert_eq!(hints.bitness.as_deref(), Some("64"));
+
+        // eyJwbGF0Zm9ybSI6IldpbmRvd3MiLCJtb2JpbGUiOmZhbHNlfQ== is {"platform":"Windows","mobile":false}
+        let hints = ClientHints::from_high_entropy_values_base64(
+            "eyJwbGF0Zm9ybSI6IldpbmRvd3MiLCJtb2JpbGUiOmZhbHNlfQ==",
+        )
+        .unwrap();
+        assert_eq!(hints.platform.as_deref(), Some("Windows"));
+        assert_eq!(hints.mobile, Some(false));
+
+        assert!(ClientHints::from_high_entropy_values_base64("not base64!").is_err());
+    }
+}
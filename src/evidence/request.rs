@@ -0,0 +1,128 @@
+use std::net::SocketAddr;
+
+use http::{HeaderMap, Request, request::Parts};
+
+use super::Evidence;
+
+impl Evidence {
+    /// Creates evidence from the headers of an HTTP request.
+    /// Headers are added as `EvidenceKind::HeaderString` and the `Cookie` headers as
+    /// `EvidenceKind::Cookie`. Values that are not valid UTF-8 or contain a NUL character
+    /// are skipped.
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        headers
+            .iter()
+            .fold(Evidence::default(), |evidence, (name, value)| {
+                evidence.add_header(name.as_str(), value.as_bytes())
+            })
+    }
+
+    /// Creates evidence from an HTTP request, with its headers and cookies as in
+    /// `Evidence::from_headers` and the parameters of the query string as
+    /// `EvidenceKind::Query`. The request does not know the address of the client,
+    /// use `Evidence::from_request_with_addr` to add it.
+    pub fn from_request<B>(request: &Request<B>) -> Self {
+        Self::from_parts(request.headers(), request.uri())
+    }
+
+    /// Like `Evidence::from_request`, with the remote address the server accepted the
+    /// connection from as the `client-ip` server evidence, e.g. the `SocketAddr` of
+    /// axum's `ConnectInfo`.
+    pub fn from_request_with_addr<B>(request: &Request<B>, remote: SocketAddr) -> Self {
+        Self::from_request(request).add_client_ip(remote.ip())
+    }
+
+    /// Like `Evidence::from_request`, for requests split into their parts.
+    pub fn from_request_parts(parts: &Parts) -> Self {
+        Self::from_parts(&parts.headers, &parts.uri)
+    }
+
+    /// Like `Evidence::from_request_with_addr`, for requests split into their parts.
+    pub fn from_request_parts_with_addr(parts: &Parts, remote: SocketAddr) -> Self {
+        Self::from_request_parts(parts).add_client_ip(remote.ip())
+    }
+
+    fn from_parts(headers: &HeaderMap, uri: &http::Uri) -> Self {
+        let evidence = Self::from_headers(headers);
+        match uri.query() {
+            Some(query) => evidence.add_query_string(query),
+            None => evidence,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{Ipv6Addr, SocketAddrV6};
+
+    use http::HeaderValue;
+
+    use crate::evidence::{EvidenceCollection, EvidenceKind};
+
+    use super::*;
+
+    fn items(evidence: &Evidence) -> Vec<(EvidenceKind, &str, &str)> {
+        let EvidenceCollection::EvidenceKeyValues(items) = &evidence.data else {
+            panic!("expected key values");
+        };
+        items
+            .iter()
+            .map(|item| {
+                (
+                    item.kind,
+                    item.field.to_str().unwrap(),
+                    item.value.to_str().unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn evidence_from_request() {
+        let request = Request::builder()
+            .uri("https://example.com/?51D_deviceId=12280-0-0-0&name=a%20b")
+            .header("user-agent", "Mozilla/5.0 (iPhone)")
+            .header("sec-ch-ua-mobile", "?1")
+            .header("cookie", "51D_ProfileIds=12280; session=\"x\"")
+            .header("x-binary", HeaderValue::from_bytes(b"\xff").unwrap())
+            .body(())
+            .unwrap();
+        // as accepted by the listener of the server
+        let remote = SocketAddr::V6(SocketAddrV6::new(Ipv6Addr::LOCALHOST, 8080, 0, 0));
+
+        // the binary header is skipped
+        let headers = [
+            (
+                EvidenceKind::HeaderString,
+                "user-agent",
+                "Mozilla/5.0 (iPhone)",
+            ),
+            (EvidenceKind::HeaderString, "sec-ch-ua-mobile", "?1"),
+            (EvidenceKind::Cookie, "51D_ProfileIds", "12280"),
+            (EvidenceKind::Cookie, "session", "x"),
+        ];
+        let query = [
+            (EvidenceKind::Query, "51D_deviceId", "12280-0-0-0"),
+            (EvidenceKind::Query, "name", "a b"),
+        ];
+        assert_eq!(items(&Evidence::from_headers(request.headers())), headers);
+        assert_eq!(
+            items(&Evidence::from_request(&request)),
+            [&headers[..], &query[..]].concat()
+        );
+
+        let evidence = Evidence::from_request_with_addr(&request, remote);
+        let client_ip = [(EvidenceKind::Server, "client-ip", "::1")];
+        assert_eq!(
+            items(&evidence),
+            [&headers[..], &query[..], &client_ip[..]].concat()
+        );
+
+        let (parts, ()) = request.into_parts();
+        let evidence = Evidence::from_request_parts_with_addr(&parts, remote);
+        assert_eq!(
+            items(&evidence),
+            [&headers[..], &query[..], &client_ip[..]].concat()
+        );
+    }
+}
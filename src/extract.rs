@@ -0,0 +1,223 @@
+use std::{error::Error, fmt::Display};
+
+use crate::{
+    device::Device,
+    device_info::DeviceInfo,
+    evidence::Evidence,
+    hash_engine::{HashEngine, HashManagerError, ResultsHash},
+};
+
+/// The rejection of the `Device` and `DeviceInfo` extractors.
+#[derive(Debug)]
+pub enum DetectionRejection {
+    /// The application state does not contain a `HashEngine`.
+    MissingEngine,
+    /// The engine failed to process the evidence of the request.
+    Process(HashManagerError),
+}
+
+impl Display for DetectionRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DetectionRejection::MissingEngine => {
+                write!(f, "no hash engine in the application state.")
+            }
+            DetectionRejection::Process(_) => write!(f, "device detection failed."),
+        }
+    }
+}
+
+impl Error for DetectionRejection {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            DetectionRejection::MissingEngine => None,
+            DetectionRejection::Process(e) => Some(e),
+        }
+    }
+}
+
+/// A result of the detection that can be extracted from a request.
+trait FromDetection: Sized {
+    fn from_detection(results: &mut ResultsHash<'_>) -> Self;
+}
+
+impl FromDetection for Device {
+    fn from_detection(results: &mut ResultsHash<'_>) -> Self {
+        Device::from_results(results)
+    }
+}
+
+impl FromDetection for DeviceInfo {
+    fn from_detection(results: &mut ResultsHash<'_>) -> Self {
+        results.to_owned_result(results.selected_properties())
+    }
+}
+
+fn detect<T: FromDetection>(
+    engine: &HashEngine,
+    evidence: &Evidence,
+) -> Result<T, DetectionRejection> {
+    let mut results = engine
+        .process(evidence)
+        .map_err(DetectionRejection::Process)?;
+    Ok(T::from_detection(&mut results))
+}
+
+#[cfg(feature = "axum")]
+mod axum {
+    use std::sync::Arc;
+
+    use axum_core::{
+        extract::{FromRef, FromRequestParts},
+        response::{IntoResponse, Response},
+    };
+    use http::{StatusCode, request::Parts};
+
+    use super::*;
+
+    /// Extracts the `Device` of the request, with the `Arc<HashEngine>` of the state.
+    /// The detection runs synchronously, see `AsyncHashEngine` for engines that read
+    /// from the data file.
+    impl<S> FromRequestParts<S> for Device
+    where
+        S: Send + Sync,
+        Arc<HashEngine>: FromRef<S>,
+    {
+        type Rejection = DetectionRejection;
+
+        async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+            let engine = Arc::<HashEngine>::from_ref(state);
+            detect(&engine, &Evidence::from_request_parts(parts))
+        }
+    }
+
+    /// Extracts the `DeviceInfo` with all selected properties of the request, with the
+    /// `Arc<HashEngine>` of the state. The detection runs synchronously, see
+    /// `AsyncHashEngine` for engines that read from the data file.
+    impl<S> FromRequestParts<S> for DeviceInfo
+    where
+        S: Send + Sync,
+        Arc<HashEngine>: FromRef<S>,
+    {
+        type Rejection = DetectionRejection;
+
+        async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+            let engine = Arc::<HashEngine>::from_ref(state);
+            detect(&engine, &Evidence::from_request_parts(parts))
+        }
+    }
+
+    impl IntoResponse for DetectionRejection {
+        fn into_response(self) -> Response {
+            (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()).into_response()
+        }
+    }
+}
+
+#[cfg(feature = "actix-web")]
+mod actix {
+    use std::future::{Ready, ready};
+
+    use actix_web::{FromRequest, HttpRequest, ResponseError, dev::Payload, web::Data};
+
+    use super::*;
+
+    fn detect_actix<T: FromDetection>(request: &HttpRequest) -> Result<T, DetectionRejection> {
+        let engine = request
+            .app_data::<Data<HashEngine>>()
+            .ok_or(DetectionRejection::MissingEngine)?;
+        detect(engine, &Evidence::from_actix_request(request))
+    }
+
+    /// Extracts the `Device` of the request, with the `Data<HashEngine>` of the app.
+    /// The detection runs synchronously, see `AsyncHashEngine` for engines that read
+    /// from the data file.
+    impl FromRequest for Device {
+        type Error = DetectionRejection;
+        type Future = Ready<Result<Self, Self::Error>>;
+
+        fn from_request(request: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+            ready(detect_actix(request))
+        }
+    }
+
+    /// Extracts the `DeviceInfo` with all selected properties of the request, with the
+    /// `Data<HashEngine>` of the app. The detection runs synchronously, see
+    /// `AsyncHashEngine` for engines that read from the data file.
+    impl FromRequest for DeviceInfo {
+        type Error = DetectionRejection;
+        type Future = Ready<Result<Self, Self::Error>>;
+
+        fn from_request(request: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+            ready(detect_actix(request))
+        }
+    }
+
+    impl ResponseError for DetectionRejection {}
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        hash_engine::HashConfig,
+        test_support::{self, IPHONE_UA},
+    };
+
+    use super::*;
+
+    #[cfg(feature = "axum")]
+    #[test]
+    fn axum_extractors() {
+        use std::{
+            future::Future,
+            pin::pin,
+            sync::Arc,
+            task::{Context, Poll, Waker},
+        };
+
+        use axum_core::extract::FromRequestParts;
+
+        let engine = Arc::new(test_support::engine(HashConfig::HighPerformance));
+        let (mut parts, ()) = http::Request::builder()
+            .header("user-agent", IPHONE_UA)
+            .body(())
+            .unwrap()
+            .into_parts();
+
+        let mut cx = Context::from_waker(Waker::noop());
+        let future = pin!(Device::from_request_parts(&mut parts, &engine));
+        let Poll::Ready(Ok(device)) = future.poll(&mut cx) else {
+            panic!("the extractor to detect the device");
+        };
+        assert_eq!(device.platform_name.as_deref(), Some("iOS"));
+
+        let future = pin!(DeviceInfo::from_request_parts(&mut parts, &engine));
+        let Poll::Ready(Ok(info)) = future.poll(&mut cx) else {
+            panic!("the extractor to detect the device");
+        };
+        assert_eq!(info.get_bool("IsMobile"), Some(true));
+    }
+
+    #[cfg(feature = "actix-web")]
+    #[test]
+    fn actix_extractors() {
+        use actix_web::{FromRequest, dev::Payload, test::TestRequest, web::Data};
+
+        let request = TestRequest::default()
+            .insert_header(("user-agent", IPHONE_UA))
+            .app_data(Data::new(test_support::engine(HashConfig::HighPerformance)))
+            .to_http_request();
+        let device = Device::from_request(&request, &mut Payload::None)
+            .into_inner()
+            .unwrap();
+        assert_eq!(device.platform_name.as_deref(), Some("iOS"));
+
+        let request = TestRequest::default()
+            .insert_header(("user-agent", IPHONE_UA))
+            .to_http_request();
+        assert!(matches!(
+            DeviceInfo::from_request(&request, &mut Payload::None).into_inner(),
+            Err(DetectionRejection::MissingEngine)
+        ));
+    }
+}
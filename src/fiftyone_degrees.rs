@@ -53,6 +53,26 @@ impl Error for Exception {}
 unsafe impl Sync for Exception {}
 unsafe impl Send for Exception {}
 
+impl Default for fiftyoneDegreesCollectionItem {
+    fn default() -> Self {
+        // equivalent to fiftyoneDegreesDataReset with no handle or collection set
+        unsafe { std::mem::zeroed() }
+    }
+}
+
+impl fiftyoneDegreesCollectionItem {
+    /// Returns the item to the collection it was retrieved from.
+    /// Must be called once for every item filled by a collection get function.
+    pub unsafe fn release(&mut self) {
+        if self.collection.is_null() {
+            return;
+        }
+        if let Some(release) = (*self.collection).release {
+            release(self);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
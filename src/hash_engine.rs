@@ -1,39 +1,419 @@
 use std::{
     cell::UnsafeCell,
     error::Error,
-    ffi::{CStr, CString},
+    ffi::{CStr, CString, c_char, c_void},
     fmt::{Display, Write},
+    marker::PhantomData,
     mem,
     path::{Path, PathBuf},
+    slice,
+    sync::{
+        Arc, Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
 };
 
 use crate::{
-    evidence::{Evidence, EvidenceRef},
+    dataset::{self, DataSetInfo, DataSetRef, PropertyInfo},
+    device::DeviceProperty,
+    device_info::{DeviceInfo, PropertyValue},
+    evidence::{Evidence, EvidenceArray, EvidenceKeys},
     fiftyone_degrees::{
         self, Exception, ResourceManager, fiftyone_degrees_string_t,
         fiftyoneDegreesHashGetDeviceIdFromResults, fiftyoneDegreesResultsHashCreate,
         fiftyoneDegreesResultsHashFree, fiftyoneDegreesResultsHashFromEvidence,
         fiftyoneDegreesResultsHashGetValues,
     },
+    metrics::{MatchMethod, MetricProperty, Metrics, ResultMetrics},
+    property::{PropertyHandle, PropertyKey},
+    response::{self, ResponseHeaders},
+    values::{self, NoValueReason, ValueError, ValueType, Version},
 };
 
+/// The configuration presets of the hash engine.
+/// See [`HashConfigBuilder`] to adjust single values of a preset.
 #[derive(Clone, Copy, Debug)]
 pub enum HashConfig {
+    /// The whole data set is loaded into memory.
     InMemory,
+    /// The whole data set is loaded into memory, with the graph optimized for speed.
     HighPerformance,
+    /// Everything is read from the data file on demand, nothing is cached.
     LowMemory,
+    /// The data set is read from the file on demand and cached.
+    Balanced,
+    /// Like `Balanced`, but reads from a private temporary copy of the data file,
+    /// so the original file can be replaced on disk while the engine runs.
+    BalancedTemp,
+    /// Loads the data set into memory as a single continuous block.
     SingleLoaded,
 }
 
+/// The collections of the hash data set that can be cached or loaded into memory individually.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DataCollection {
+    Strings,
+    Components,
+    Maps,
+    Properties,
+    Values,
+    Profiles,
+    RootNodes,
+    Nodes,
+    ProfileOffsets,
+}
+
+/// Memory configuration of a single collection.
+/// `loaded` items are read into memory when the data set is initialized,
+/// `capacity` sets the size of the cache used for all other items.
+/// Values that are `None` keep the value of the preset, e.g.
+/// `CollectionConfig { capacity: Some(1000), ..Default::default() }` only changes the cache size.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CollectionConfig {
+    pub loaded: Option<u32>,
+    pub capacity: Option<u32>,
+    pub concurrency: Option<u16>,
+}
+
+/// A builder to fine tune the configuration of the hash engine.
+/// It starts with the values of one of the `HashConfig` presets, every value set on the builder
+/// overrides the value of the preset.
+/// See: [51Degrees Docs](https://51degrees.com/device-detection-cxx/4.4/structfifty_one_degrees_config_hash.html)
+///
+#[derive(Clone, Debug)]
+pub struct HashConfigBuilder {
+    preset: HashConfig,
+    difference: Option<i32>,
+    drift: Option<i32>,
+    use_performance_graph: Option<bool>,
+    use_predictive_graph: Option<bool>,
+    concurrency: Option<u16>,
+    allow_unmatched: Option<bool>,
+    trace_route: Option<bool>,
+    update_matched_user_agent: Option<bool>,
+    use_temp_file: Option<bool>,
+    reuse_temp_file: Option<bool>,
+    temp_dirs: Vec<PathBuf>,
+    collections: Vec<(DataCollection, CollectionConfig)>,
+}
+
+impl From<HashConfig> for HashConfigBuilder {
+    fn from(preset: HashConfig) -> Self {
+        Self::new(preset)
+    }
+}
+
+impl HashConfigBuilder {
+    /// Creates a new builder with the values of the given preset.
+    pub fn new(preset: HashConfig) -> Self {
+        Self {
+            preset,
+            difference: None,
+            drift: None,
+            use_performance_graph: None,
+            use_predictive_graph: None,
+            concurrency: None,
+            allow_unmatched: None,
+            trace_route: None,
+            update_matched_user_agent: None,
+            use_temp_file: None,
+            reuse_temp_file: None,
+            temp_dirs: Vec::default(),
+            collections: Vec::default(),
+        }
+    }
+
+    /// Sets the maximum difference in hash value that is still considered a match.
+    pub fn difference(mut self, difference: i32) -> Self {
+        self.difference = Some(difference);
+        self
+    }
+
+    /// Sets the maximum drift of a hash position that is still considered a match.
+    pub fn drift(mut self, drift: i32) -> Self {
+        self.drift = Some(drift);
+        self
+    }
+
+    /// Enables or disables the performance optimized graph.
+    pub fn use_performance_graph(mut self, enabled: bool) -> Self {
+        self.use_performance_graph = Some(enabled);
+        self
+    }
+
+    /// Enables or disables the predictive optimized graph.
+    pub fn use_predictive_graph(mut self, enabled: bool) -> Self {
+        self.use_predictive_graph = Some(enabled);
+        self
+    }
+
+    /// Sets the expected number of concurrent operations for all collections.
+    /// Collection specific values set with `collection` take precedence.
+    pub fn concurrency(mut self, concurrency: u16) -> Self {
+        self.concurrency = Some(concurrency);
+        self
+    }
+
+    /// If enabled the default profile of a component is returned when there is no match,
+    /// otherwise the results contain no values for the component.
+    pub fn allow_unmatched(mut self, enabled: bool) -> Self {
+        self.allow_unmatched = Some(enabled);
+        self
+    }
+
+    /// Records the route through the graph during the detection, for debugging purposes.
+    pub fn trace_route(mut self, enabled: bool) -> Self {
+        self.trace_route = Some(enabled);
+        self
+    }
+
+    /// Stores the matched characters of the user agent in the results.
+    pub fn update_matched_user_agent(mut self, enabled: bool) -> Self {
+        self.update_matched_user_agent = Some(enabled);
+        self
+    }
+
+    /// If enabled the engine creates a temporary copy of the data file and reads from it,
+    /// so the original file can be replaced on disk, e.g. before calling
+    /// `HashEngine::reload_from_original_file`. Enabled by the `BalancedTemp` preset.
+    pub fn use_temp_file(mut self, enabled: bool) -> Self {
+        self.use_temp_file = Some(enabled);
+        self
+    }
+
+    /// If enabled an existing temporary copy of the same data file is used
+    /// instead of creating a new one.
+    pub fn reuse_temp_file(mut self, enabled: bool) -> Self {
+        self.reuse_temp_file = Some(enabled);
+        self
+    }
+
+    /// Sets the directories the temporary copies of the data file are created in.
+    /// Defaults to the directory of the data file.
+    pub fn temp_dirs(mut self, dirs: &[PathBuf]) -> Self {
+        self.temp_dirs = dirs.to_vec();
+        self
+    }
+
+    /// Sets the memory configuration of a single collection, replacing a configuration
+    /// set for the same collection before.
+    pub fn collection(mut self, collection: DataCollection, config: CollectionConfig) -> Self {
+        self.collections.retain(|(c, _)| *c != collection);
+        self.collections.push((collection, config));
+        self
+    }
+
+    /// Checks the configured values without initializing an engine.
+    pub fn validate(&self) -> Result<(), HashConfigError> {
+        self.build().map(|_| ())
+    }
+
+    fn build(&self) -> Result<fiftyone_degrees::fiftyoneDegreesConfigHash, HashConfigError> {
+        let mut config = unsafe {
+            match self.preset {
+                HashConfig::LowMemory => fiftyone_degrees::fiftyoneDegreesHashLowMemoryConfig,
+                HashConfig::InMemory => fiftyone_degrees::fiftyoneDegreesHashInMemoryConfig,
+                HashConfig::HighPerformance => {
+                    fiftyone_degrees::fiftyoneDegreesHashHighPerformanceConfig
+                }
+                HashConfig::Balanced => fiftyone_degrees::fiftyoneDegreesHashBalancedConfig,
+                HashConfig::BalancedTemp => fiftyone_degrees::fiftyoneDegreesHashBalancedTempConfig,
+                HashConfig::SingleLoaded => fiftyone_degrees::fiftyoneDegreesHashSingleLoadedConfig,
+            }
+        };
+
+        if let Some(difference) = self.difference {
+            if difference < 0 {
+                return Err(HashConfigError::NegativeDifference(difference));
+            }
+            config.difference = difference;
+        }
+        if let Some(drift) = self.drift {
+            if drift < 0 {
+                return Err(HashConfigError::NegativeDrift(drift));
+            }
+            config.drift = drift;
+        }
+        if let Some(enabled) = self.use_performance_graph {
+            config.usePerformanceGraph = enabled;
+        }
+        if let Some(enabled) = self.use_predictive_graph {
+            config.usePredictiveGraph = enabled;
+        }
+        if !config.usePerformanceGraph && !config.usePredictiveGraph {
+            return Err(HashConfigError::NoGraph);
+        }
+        if let Some(enabled) = self.allow_unmatched {
+            config.b.allowUnmatched = enabled;
+        }
+        if let Some(enabled) = self.trace_route {
+            config.traceRoute = enabled;
+        }
+        if let Some(enabled) = self.update_matched_user_agent {
+            config.b.updateMatchedUserAgent = enabled;
+        }
+        if let Some(enabled) = self.use_temp_file {
+            config.b.b.useTempFile = enabled;
+        }
+        if let Some(enabled) = self.reuse_temp_file {
+            config.b.b.reuseTempFile = enabled;
+        }
+        if let Some(concurrency) = self.concurrency {
+            if concurrency == 0 {
+                return Err(HashConfigError::ZeroConcurrency);
+            }
+            for collection in ALL_COLLECTIONS {
+                collection_config(&mut config, collection).concurrency = concurrency;
+            }
+        }
+        for (collection, value) in &self.collections {
+            let target = collection_config(&mut config, *collection);
+            if let Some(loaded) = value.loaded {
+                target.loaded = loaded;
+            }
+            if let Some(capacity) = value.capacity {
+                target.capacity = capacity;
+            }
+            if let Some(concurrency) = value.concurrency {
+                if concurrency == 0 {
+                    return Err(HashConfigError::ZeroConcurrency);
+                }
+                target.concurrency = concurrency;
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+const ALL_COLLECTIONS: [DataCollection; 9] = [
+    DataCollection::Strings,
+    DataCollection::Components,
+    DataCollection::Maps,
+    DataCollection::Properties,
+    DataCollection::Values,
+    DataCollection::Profiles,
+    DataCollection::RootNodes,
+    DataCollection::Nodes,
+    DataCollection::ProfileOffsets,
+];
+
+fn collection_config(
+    config: &mut fiftyone_degrees::fiftyoneDegreesConfigHash,
+    collection: DataCollection,
+) -> &mut fiftyone_degrees::fiftyoneDegreesCollectionConfig {
+    match collection {
+        DataCollection::Strings => &mut config.strings,
+        DataCollection::Components => &mut config.components,
+        DataCollection::Maps => &mut config.maps,
+        DataCollection::Properties => &mut config.properties,
+        DataCollection::Values => &mut config.values,
+        DataCollection::Profiles => &mut config.profiles,
+        DataCollection::RootNodes => &mut config.rootNodes,
+        DataCollection::Nodes => &mut config.nodes,
+        DataCollection::ProfileOffsets => &mut config.profileOffsets,
+    }
+}
+
+/// The temporary directories referenced by the configuration of the engine.
+/// The library keeps the pointers of the config to create copies of the data file on reload,
+/// so they have to live as long as the engine.
+struct TempDirs {
+    _dirs: Vec<CString>,
+    ptrs: Vec<*const c_char>,
+}
+
+impl TempDirs {
+    fn new(dirs: &[PathBuf]) -> Self {
+        let dirs: Vec<CString> = dirs
+            .iter()
+            .map(|dir| {
+                CString::new(dir.as_os_str().as_encoded_bytes())
+                    .expect("path to cstring conversion failed")
+            })
+            .collect();
+        let ptrs = dirs.iter().map(|dir| dir.as_ptr()).collect();
+
+        Self { _dirs: dirs, ptrs }
+    }
+
+    fn apply(&mut self, config: &mut fiftyone_degrees::fiftyoneDegreesConfigHash) {
+        if self.ptrs.is_empty() {
+            return;
+        }
+        config.b.b.tempDirs = self.ptrs.as_mut_ptr();
+        config.b.b.tempDirCount = self.ptrs.len() as _;
+    }
+}
+
+/// An invalid value set on the `HashConfigBuilder`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashConfigError {
+    NegativeDifference(i32),
+    NegativeDrift(i32),
+    NoGraph,
+    ZeroConcurrency,
+}
+
+impl Display for HashConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HashConfigError::NegativeDifference(value) => {
+                write!(f, "difference must not be negative, got {}.", value)
+            }
+            HashConfigError::NegativeDrift(value) => {
+                write!(f, "drift must not be negative, got {}.", value)
+            }
+            HashConfigError::NoGraph => write!(
+                f,
+                "at least one of the performance or predictive graph must be enabled."
+            ),
+            HashConfigError::ZeroConcurrency => write!(f, "concurrency must be at least 1."),
+        }
+    }
+}
+
+impl Error for HashConfigError {}
+
 /// A builder to configure the hash engine.
-/// A a path to the hash file is mandatory.
+/// Either a path to the hash file or the hash data in memory is mandatory.
 /// The performance configuration can be set with the `hash_config` function,
 /// `LowMemory` is the default.
 ///
 pub struct HashEngineBuilder {
-    hash_config: HashConfig,
-    hash_file: PathBuf,
-    properties: Vec<&'static str>,
+    hash_config: HashConfigBuilder,
+    source: DataSource,
+    properties: Vec<String>,
+}
+
+enum DataSource {
+    File(PathBuf),
+    Memory(HashData),
+}
+
+/// Hash data held in memory.
+/// The C library reads the data set directly from the buffer, so it must not move or be
+/// freed while a data set created from it is in use. Everything that holds a reference to
+/// the data set also holds an `Arc` of its buffer.
+struct HashData(Box<dyn AsRef<[u8]> + Send + Sync>);
+
+impl HashData {
+    fn new<T: AsRef<[u8]> + Send + Sync + 'static>(data: T) -> Self {
+        Self(Box::new(data))
+    }
+
+    fn bytes(&self) -> &[u8] {
+        (*self.0).as_ref()
+    }
+}
+
+/// The buffers of the data sets loaded from memory that readers may be using.
+/// The library swaps in the data set of a reload before the engine can store its buffer
+/// as active, readers that acquire a data set meanwhile keep the staged buffer as well.
+#[derive(Clone, Default)]
+struct Buffers {
+    active: Option<Arc<HashData>>,
+    staged: Option<Arc<HashData>>,
 }
 
 /// A wrapper type for the hash device detection.
@@ -41,8 +421,39 @@ pub struct HashEngineBuilder {
 pub struct HashEngine {
     manager: Box<UnsafeCell<ResourceManager>>,
     _properties: CString,
+    _temp_dirs: TempDirs,
+    // The buffers of the data sets loaded from memory. Acquiring a data set and cloning the
+    // buffers happens under the read lock. Reloads only take the write lock to stage a new
+    // buffer before, and to replace the active one after the library swapped the data sets,
+    // so the buffer of a replaced data set is freed with the last reference to it.
+    data: RwLock<Buffers>,
+    // reloads run one at a time, each staging at most one buffer
+    reload: Mutex<()>,
+    // counts the successful reloads, so reused results notice a new data set cheaply
+    generation: AtomicU64,
+    // the largest concurrency of the collections, the size of the file handle pool
+    concurrency: u16,
+    // whether the config loads data sets from files into memory
+    all_in_memory: bool,
+    // whether the active data set is held in memory, it changes when a reload switches
+    // between a file and a buffer
+    in_memory: AtomicBool,
 }
 
+// SAFETY: The resource manager of the C library is designed for concurrent use.
+// Every operation acquires its own reference to the active data set and the reference count
+// is maintained atomically, a reload swaps the active data set atomically and frees the old
+// one once the last reference is released. The manager is only accessed through `&mut` in
+// `init` and `drop`, where the engine can not be shared.
+// Data sets that read from the file share a pool of `concurrency` file handles, which is
+// taken from and returned to atomically. An operation that finds no free handle fails with
+// an exception instead of sharing one, so more threads than the configured concurrency
+// only cause failed detections, see `HashEngine::concurrency`.
+// The property string and the temporary directories are not modified after `init` and only
+// read by the library, the buffers of the data sets are guarded by a lock.
+unsafe impl Send for HashEngine {}
+unsafe impl Sync for HashEngine {}
+
 impl Drop for HashEngine {
     fn drop(&mut self) {
         unsafe {
@@ -55,16 +466,28 @@ impl HashEngineBuilder {
     /// Creates a new builder based on the location of the device_detection.hash file.
     pub fn new(hash_file: &Path) -> Self {
         Self {
-            hash_config: HashConfig::LowMemory,
-            hash_file: hash_file.to_owned(),
+            hash_config: HashConfig::LowMemory.into(),
+            source: DataSource::File(hash_file.to_owned()),
+            properties: Vec::default(),
+        }
+    }
+
+    /// Creates a new builder based on the content of a device_detection.hash file held in memory,
+    /// e.g. a `Vec<u8>` downloaded from a remote location or a `&'static [u8]` from `include_bytes!`.
+    /// The engine takes ownership of the buffer and keeps it alive as long as it is used.
+    pub fn from_memory<T: AsRef<[u8]> + Send + Sync + 'static>(hash_data: T) -> Self {
+        Self {
+            hash_config: HashConfig::LowMemory.into(),
+            source: DataSource::Memory(HashData::new(hash_data)),
             properties: Vec::default(),
         }
     }
 
-    /// Sets the performance configuration of the hash engine.
+    /// Sets the performance configuration of the hash engine,
+    /// either one of the `HashConfig` presets or an adjusted `HashConfigBuilder`.
     /// Defaults to `LowMemory`
-    pub fn hash_config(mut self, config: HashConfig) -> Self {
-        self.hash_config = config;
+    pub fn hash_config<C: Into<HashConfigBuilder>>(mut self, config: C) -> Self {
+        self.hash_config = config.into();
         self
     }
 
@@ -72,15 +495,25 @@ impl HashEngineBuilder {
     /// Defaults to all properties available.
     /// See: [51Degrees Docs](https://51degrees.com/device-detection-cxx/4.4/group___fifty_one_degrees_properties.html#gafe718e9dd0c8b93c755337a6f17b2b60)
     ///
-    pub fn set_properties(mut self, properties: &[&'static str]) -> Self {
-        self.properties = properties.to_vec();
+    pub fn set_properties<I, S>(mut self, properties: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.properties = properties
+            .into_iter()
+            .map(|property| property.as_ref().to_string())
+            .collect();
         self
     }
 
     /// Allocates and initializes the hash engine.
     pub fn init(self) -> Result<HashEngine, HashManagerError> {
-        let data_file = CString::new(self.hash_file.as_os_str().as_encoded_bytes())
-            .expect("path to cstring conversion failed");
+        let mut config = self.hash_config.build().map_err(|e| HashManagerError {
+            kind: HashManagerErrorKind::Config(e),
+        })?;
+        let mut temp_dirs = TempDirs::new(&self.hash_config.temp_dirs);
+        temp_dirs.apply(&mut config);
 
         let mut buf: String = String::default();
 
@@ -103,22 +536,34 @@ impl HashEngineBuilder {
                 default.string = properties.as_ptr();
             }
 
-            let mut config = match self.hash_config {
-                HashConfig::LowMemory => fiftyone_degrees::fiftyoneDegreesHashLowMemoryConfig,
-                HashConfig::InMemory => fiftyone_degrees::fiftyoneDegreesHashInMemoryConfig,
-                HashConfig::HighPerformance => {
-                    fiftyone_degrees::fiftyoneDegreesHashHighPerformanceConfig
+            match &self.source {
+                DataSource::File(hash_file) => {
+                    let data_file = CString::new(hash_file.as_os_str().as_encoded_bytes())
+                        .expect("path to cstring conversion failed");
+
+                    fiftyone_degrees::fiftyoneDegreesHashInitManagerFromFile(
+                        manager.get_mut(),
+                        &mut config,
+                        &mut default,
+                        data_file.as_ptr(),
+                        &mut exception,
+                    )
                 }
-                HashConfig::SingleLoaded => fiftyone_degrees::fiftyoneDegreesHashSingleLoadedConfig,
-            };
+                DataSource::Memory(hash_data) => {
+                    // the buffer is owned by the engine and must not be freed by the library
+                    config.b.b.freeData = false;
+                    let bytes = hash_data.bytes();
 
-            fiftyone_degrees::fiftyoneDegreesHashInitManagerFromFile(
-                manager.get_mut(),
-                &mut config,
-                &mut default,
-                data_file.as_ptr(),
-                &mut exception,
-            )
+                    fiftyone_degrees::fiftyoneDegreesHashInitManagerFromMemory(
+                        manager.get_mut(),
+                        &mut config,
+                        &mut default,
+                        bytes.as_ptr().cast_mut().cast::<c_void>(),
+                        bytes.len() as _,
+                        &mut exception,
+                    )
+                }
+            }
         };
 
         if !exception.is_ok() {
@@ -133,9 +578,32 @@ impl HashEngineBuilder {
             });
         }
 
+        // data sets initialized from memory never read from a file
+        let all_in_memory = config.b.b.allInMemory;
+        let concurrency = ALL_COLLECTIONS
+            .into_iter()
+            .map(|collection| collection_config(&mut config, collection).concurrency)
+            .max()
+            .unwrap_or(1);
+        let in_memory = all_in_memory || matches!(self.source, DataSource::Memory(_));
+        let data = Buffers {
+            active: match self.source {
+                DataSource::File(_) => None,
+                DataSource::Memory(hash_data) => Some(Arc::new(hash_data)),
+            },
+            staged: None,
+        };
+
         Ok(HashEngine {
             manager,
             _properties: properties,
+            _temp_dirs: temp_dirs,
+            data: RwLock::new(data),
+            reload: Mutex::new(()),
+            generation: AtomicU64::new(0),
+            concurrency,
+            all_in_memory,
+            in_memory: AtomicBool::new(in_memory),
         })
     }
 }
@@ -143,9 +611,24 @@ impl HashEngineBuilder {
 impl<'a> HashEngine {
     /// Allocates and fills a result with the evidence provided.
     pub fn process(&'a self, evidence: &'_ Evidence) -> Result<ResultsHash<'a>, HashManagerError> {
-        let max_len = evidence.len() as u32;
+        let mut results = self.create_results(evidence.len())?;
+        self.process_into(&mut results, evidence)?;
+        Ok(results)
+    }
+
+    /// Allocates results for evidence with up to `evidence_capacity` values, that can be
+    /// filled repeatedly with `process_into`.
+    pub fn create_results(
+        &self,
+        evidence_capacity: usize,
+    ) -> Result<ResultsHash<'_>, HashManagerError> {
+        let capacity = evidence_capacity as u32;
+        // read before the data set is acquired, a reload in between only recreates
+        // the results once more
+        let generation = self.generation.load(Ordering::Acquire);
+        let data = self.data();
         let result_ptr = unsafe {
-            fiftyoneDegreesResultsHashCreate(self.manager.get().cast(), max_len, max_len)
+            fiftyoneDegreesResultsHashCreate(self.manager.get().cast(), capacity, capacity)
         };
         if result_ptr.is_null() {
             return Err(HashManagerError {
@@ -153,14 +636,37 @@ impl<'a> HashEngine {
             });
         }
 
-        let evidence_ref = EvidenceRef::new(evidence);
+        Ok(ResultsHash {
+            result_ptr,
+            engine: PhantomData,
+            metric_value: String::default(),
+            capacity: evidence_capacity,
+            generation,
+            evidence: EvidenceArray::with_capacity(evidence_capacity),
+            _data: data.clone(),
+        })
+    }
+
+    /// Replaces the content of the results with the detection for the evidence provided.
+    /// Reusing the results, and the evidence array they hold, avoids the allocations of
+    /// `process` for every evidence.
+    /// The results are allocated again if the evidence exceeds their capacity, or the engine
+    /// reloaded its data since they were created.
+    pub fn process_into(
+        &'a self,
+        results: &mut ResultsHash<'a>,
+        evidence: &Evidence,
+    ) -> Result<(), HashManagerError> {
+        if evidence.len() > results.capacity
+            || results.generation != self.generation.load(Ordering::Acquire)
+        {
+            *results = self.create_results(evidence.len().max(results.capacity))?;
+        }
+
+        let kv_array = results.evidence.fill(evidence);
         let mut exception = Exception::default();
         unsafe {
-            fiftyoneDegreesResultsHashFromEvidence(
-                result_ptr,
-                evidence_ref.kv_array,
-                &mut exception,
-            )
+            fiftyoneDegreesResultsHashFromEvidence(results.result_ptr, kv_array, &mut exception)
         };
 
         if !exception.is_ok() {
@@ -168,31 +674,294 @@ impl<'a> HashEngine {
                 kind: HashManagerErrorKind::Process(exception),
             });
         }
+        Ok(())
+    }
 
-        Ok(ResultsHash {
-            result_ptr,
-            manager: self,
-        })
+    /// Processes all evidence and extracts the values of the given properties into owned rows,
+    /// with one value per property in the order of `properties`. The values of list properties
+    /// are joined by `,`, properties without a value are None.
+    ///
+    /// A single results instance is allocated for the whole batch and the property names are
+    /// resolved once, instead of for every call like `process` does.
+    pub fn process_batch<'e, P: PropertyKey>(
+        &'e self,
+        evidence: &'e [Evidence],
+        properties: &[P],
+    ) -> impl Iterator<Item = Result<Vec<Option<String>>, HashManagerError>> + use<'e, P> {
+        let handles = self.batch_handles(properties);
+        let capacity = batch_capacity(evidence);
+        let mut results = None;
+
+        evidence
+            .iter()
+            .map(move |evidence| self.process_row(&mut results, capacity, evidence, &handles))
+    }
+
+    /// Like `process_batch`, but processes the evidence in parallel on the rayon thread pool.
+    /// Every worker thread allocates a single results instance for its share of the batch.
+    #[cfg(feature = "rayon")]
+    pub fn par_process_batch<P: PropertyKey>(
+        &self,
+        evidence: &[Evidence],
+        properties: &[P],
+    ) -> Vec<Result<Vec<Option<String>>, HashManagerError>> {
+        use rayon::prelude::*;
+
+        let handles = self.batch_handles(properties);
+        let capacity = batch_capacity(evidence);
+
+        evidence
+            .par_iter()
+            .map_init(
+                || None,
+                |results, evidence| self.process_row(results, capacity, evidence, &handles),
+            )
+            .collect()
+    }
+
+    /// Whether the whole data set is held in memory, so processing never reads from
+    /// the data file. False for the `LowMemory` and `Balanced` configs, unless the active
+    /// data set was loaded from memory.
+    pub fn is_in_memory(&self) -> bool {
+        self.in_memory.load(Ordering::Acquire)
+    }
+
+    /// The number of detections that can read from the data file at the same time, the
+    /// largest concurrency configured for the collections. It is the size of the pool of
+    /// file handles, further detections fail instead of waiting for a free handle.
+    pub fn concurrency(&self) -> u16 {
+        self.concurrency
+    }
+
+    /// Returns information about the data set currently used by the engine,
+    /// e.g. to check if the data file is outdated.
+    pub fn dataset_info(&self) -> DataSetInfo {
+        let dataset = self.dataset();
+        unsafe { DataSetInfo::read(dataset.as_ptr()) }
+    }
+
+    /// Resolves a property of the data set currently used by the engine into a handle,
+    /// so accessing its values in the results does not need a lookup by name.
+    /// Fails for properties that are not part of the results, as well as for the metric
+    /// pseudo properties.
+    pub fn property<S: AsRef<str>>(&self, name: S) -> Result<PropertyHandle, NoValueReason> {
+        let dataset = self.dataset();
+        unsafe {
+            let index = dataset::required_property_index(dataset.as_ptr(), name.as_ref())?;
+            let name = dataset::required_property_name(dataset.as_ptr(), index)
+                .ok_or(NoValueReason::InvalidProperty)?;
+            Ok(PropertyHandle::new(
+                name.to_string_lossy().into_owned(),
+                index,
+            ))
+        }
+    }
+
+    /// Returns the metadata of all properties in the data set currently used by the engine,
+    /// including the ones not selected with `HashEngineBuilder::set_properties`.
+    pub fn properties(&self) -> Vec<PropertyInfo> {
+        let dataset = self.dataset();
+        unsafe { PropertyInfo::read_all(dataset.as_ptr()) }
     }
 
-    fn get_property_index(&self, property: &'static str) -> i32 {
-        let dataset_ref =
-            unsafe { fiftyone_degrees::fiftyoneDegreesDataSetGet(self.manager.get().cast()) };
+    /// Returns the evidence keys used by the data set currently used by the engine.
+    /// Use `Evidence::retain_keys` to drop the evidence the engine would ignore.
+    pub fn evidence_keys(&self) -> EvidenceKeys {
+        let dataset = self.dataset();
+        EvidenceKeys::new(unsafe { dataset::unique_header_names(dataset.as_ptr()) })
+    }
 
-        let c_name = CString::new(property).expect("static string to cstring");
-        let index = unsafe {
-            fiftyone_degrees::fiftyoneDegreesPropertiesGetRequiredPropertyIndexFromName(
-                (*dataset_ref).available,
-                c_name.as_ptr(),
+    /// Reloads the data set from the file the engine was initialized with.
+    /// Useful when the hash file was replaced on disk by an update.
+    ///
+    /// The new data set is swapped in atomically, results created before the reload
+    /// keep a reference to the old data set until they are dropped.
+    /// Processing continues with the old data set while the new one is loaded.
+    pub fn reload_from_original_file(&self) -> Result<(), HashManagerError> {
+        let _reload = self.reload.lock().unwrap_or_else(|e| e.into_inner());
+        let mut exception = Exception::default();
+        let status = unsafe {
+            fiftyone_degrees::fiftyoneDegreesHashReloadManagerFromOriginalFile(
+                self.manager.get(),
+                &mut exception,
             )
         };
 
-        unsafe {
-            fiftyone_degrees::fiftyoneDegreesDataSetRelease(dataset_ref);
+        reload_result(exception, status)?;
+        self.release_buffer();
+        self.reloaded(self.all_in_memory);
+        Ok(())
+    }
+
+    /// Reloads the data set from a new hash file.
+    /// Subsequent calls to `reload_from_original_file` will use the new file.
+    ///
+    /// The new data set is swapped in atomically, results created before the reload
+    /// keep a reference to the old data set until they are dropped.
+    /// Processing continues with the old data set while the new one is loaded.
+    pub fn reload_from_file(&self, hash_file: &Path) -> Result<(), HashManagerError> {
+        let data_file = CString::new(hash_file.as_os_str().as_encoded_bytes())
+            .expect("path to cstring conversion failed");
+
+        let _reload = self.reload.lock().unwrap_or_else(|e| e.into_inner());
+        let mut exception = Exception::default();
+        let status = unsafe {
+            fiftyone_degrees::fiftyoneDegreesHashReloadManagerFromFile(
+                self.manager.get(),
+                data_file.as_ptr(),
+                &mut exception,
+            )
+        };
+
+        reload_result(exception, status)?;
+        self.release_buffer();
+        self.reloaded(self.all_in_memory);
+        Ok(())
+    }
+
+    /// Reloads the data set from the content of a hash file held in memory.
+    ///
+    /// The new data set is swapped in atomically, results created before the reload
+    /// keep a reference to the old data set until they are dropped. The buffer of the old
+    /// data set is freed together with the last of them.
+    /// Processing continues with the old data set while the new one is loaded.
+    pub fn reload_from_memory<T: AsRef<[u8]> + Send + Sync + 'static>(
+        &self,
+        hash_data: T,
+    ) -> Result<(), HashManagerError> {
+        let hash_data = Arc::new(HashData::new(hash_data));
+        let _reload = self.reload.lock().unwrap_or_else(|e| e.into_inner());
+        self.data_mut().staged = Some(Arc::clone(&hash_data));
+
+        let mut exception = Exception::default();
+        let status = unsafe {
+            let bytes = hash_data.bytes();
+            fiftyone_degrees::fiftyoneDegreesHashReloadManagerFromMemory(
+                self.manager.get(),
+                bytes.as_ptr().cast_mut().cast::<c_void>(),
+                bytes.len() as _,
+                &mut exception,
+            )
+        };
+
+        let result = reload_result(exception, status);
+        let replaced = {
+            let mut data = self.data_mut();
+            let staged = data.staged.take();
+            match result {
+                Ok(()) => mem::replace(&mut data.active, staged),
+                Err(_) => staged,
+            }
+        };
+        // the last reference to a buffer is dropped outside of the lock
+        drop(replaced);
+        if result.is_ok() {
+            self.reloaded(true);
+        }
+        result
+    }
+
+    fn dataset(&self) -> ActiveDataSet<'_> {
+        let data = self.data();
+        ActiveDataSet {
+            dataset: unsafe { DataSetRef::acquire(self.manager.get()) },
+            _data: data.clone(),
+        }
+    }
+
+    // Marks a successful reload, with whether its data set is held in memory.
+    fn reloaded(&self, in_memory: bool) {
+        self.in_memory.store(in_memory, Ordering::Release);
+        self.generation.fetch_add(1, Ordering::Release);
+    }
+
+    fn data(&self) -> RwLockReadGuard<'_, Buffers> {
+        self.data.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn data_mut(&self) -> RwLockWriteGuard<'_, Buffers> {
+        self.data.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    // After a reload from a file, readers of the new data set do not need a buffer.
+    // Engines that never loaded from memory skip the write lock.
+    fn release_buffer(&self) {
+        if self.data().active.is_none() {
+            return;
         }
+        let replaced = self.data_mut().active.take();
+        drop(replaced);
+    }
+
+    // Properties that can not be resolved, like the metric pseudo properties,
+    // get an invalid index and are looked up by name.
+    fn batch_handles<P: PropertyKey>(&self, properties: &[P]) -> Vec<PropertyHandle> {
+        properties
+            .iter()
+            .map(|property| {
+                let name = property.property_name();
+                self.property(name)
+                    .unwrap_or_else(|_| PropertyHandle::new(name.to_string(), -1))
+            })
+            .collect()
+    }
+
+    fn process_row<'r>(
+        &'r self,
+        results: &mut Option<ResultsHash<'r>>,
+        capacity: usize,
+        evidence: &Evidence,
+        handles: &[PropertyHandle],
+    ) -> Result<Vec<Option<String>>, HashManagerError> {
+        let results = match results {
+            Some(results) => results,
+            None => results.insert(self.create_results(capacity)?),
+        };
+        self.process_into(results, evidence)?;
+
+        Ok(handles
+            .iter()
+            .map(|handle| {
+                results
+                    .get_values(handle)
+                    .ok()
+                    .map(|values| values.collect::<Vec<&str>>().join(","))
+            })
+            .collect())
+    }
+}
+
+/// A reference to the active data set of the engine, with the buffer it was loaded from.
+struct ActiveDataSet<'a> {
+    // released before the buffers are dropped
+    dataset: DataSetRef<'a>,
+    _data: Buffers,
+}
+
+impl ActiveDataSet<'_> {
+    fn as_ptr(&self) -> *mut fiftyone_degrees::fiftyoneDegreesDataSetHash {
+        self.dataset.as_ptr()
+    }
+}
+
+fn batch_capacity(evidence: &[Evidence]) -> usize {
+    evidence.iter().map(Evidence::len).max().unwrap_or_default()
+}
+
+fn reload_result(exception: Exception, status: u32) -> Result<(), HashManagerError> {
+    if !exception.is_ok() {
+        return Err(HashManagerError {
+            kind: HashManagerErrorKind::Reload(exception),
+        });
+    }
 
-        index
+    if status != fiftyone_degrees::EXIT_SUCCESS {
+        return Err(HashManagerError {
+            kind: HashManagerErrorKind::ReloadWithoutException(ErrStatus { status }),
+        });
     }
+
+    Ok(())
 }
 
 #[derive(Debug)]
@@ -210,6 +979,10 @@ impl Display for HashManagerError {
             HashManagerErrorKind::AllocatingResult | HashManagerErrorKind::Process(_) => {
                 write!(f, "error proccessing the evidence")
             }
+            HashManagerErrorKind::Reload(_) | HashManagerErrorKind::ReloadWithoutException(_) => {
+                write!(f, "error reloading the data set.")
+            }
+            HashManagerErrorKind::Config(_) => write!(f, "invalid hash engine configuration."),
         }
     }
 }
@@ -220,7 +993,10 @@ impl Error for HashManagerError {
             HashManagerErrorKind::AllocatingResult => None,
             HashManagerErrorKind::Init(e) => Some(e),
             HashManagerErrorKind::Process(e) => Some(e),
-            HashManagerErrorKind::WithoutException(status) => Some(status),
+            HashManagerErrorKind::Reload(e) => Some(e),
+            HashManagerErrorKind::Config(e) => Some(e),
+            HashManagerErrorKind::WithoutException(status)
+            | HashManagerErrorKind::ReloadWithoutException(status) => Some(status),
         }
     }
 }
@@ -230,7 +1006,10 @@ pub enum HashManagerErrorKind {
     AllocatingResult,
     Init(Exception),
     Process(Exception),
+    Reload(Exception),
+    Config(HashConfigError),
     WithoutException(ErrStatus),
+    ReloadWithoutException(ErrStatus),
 }
 
 #[derive(Debug)]
@@ -240,17 +1019,41 @@ pub struct ErrStatus {
 
 impl Display for ErrStatus {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "init status code: {}.", self.status)
+        write!(f, "status code: {}.", self.status)
     }
 }
 impl Error for ErrStatus {}
 
 /// A wrapper type for the ResultsHash provided by the hash engine.
+/// The results hold a reference to the data set they were created with,
+/// so they stay valid when the engine reloads its data in the meantime.
 pub struct ResultsHash<'a> {
     result_ptr: *mut fiftyone_degrees::ResultsHash,
-    manager: &'a HashEngine,
+    engine: PhantomData<&'a HashEngine>,
+    // Metric pseudo properties are not stored in the data set,
+    // references to their values point here.
+    metric_value: String,
+    capacity: usize,
+    // the reload generation of the engine when the results were created
+    generation: u64,
+    evidence: EvidenceArray,
+    // the buffers of the data set of the results, dropped after the results are freed
+    _data: Buffers,
+}
+
+/// Where the value of a requested property comes from.
+#[derive(Clone, Copy)]
+enum Lookup {
+    /// The required property index in the data set of the results.
+    Property(i32),
+    Metric(MetricProperty),
 }
 
+// SAFETY: The results are owned exclusively by the wrapper and keep the data set they
+// reference alive with an atomic reference count, so they can be moved to and dropped on
+// another thread. Reading values mutates the results, which requires `&mut self`.
+unsafe impl Send for ResultsHash<'_> {}
+
 impl Drop for ResultsHash<'_> {
     fn drop(&mut self) {
         unsafe {
@@ -265,90 +1068,1061 @@ where
 {
     /// Looks up the 51 Degrees device ID and returns a copy.
     pub fn get_device_id(&'b mut self) -> Option<String> {
-        let mut id_buf = [0u8; 512];
-        let mut exception = Exception::default();
-        unsafe {
-            fiftyoneDegreesHashGetDeviceIdFromResults(
-                self.result_ptr,
-                id_buf.as_mut_ptr() as *mut i8,
-                id_buf.len(),
-                &mut exception,
-            ) as usize
+        self.device_id()
+    }
+
+    /// Returns the match metrics of the detection, with one entry for every evidence header
+    /// the engine matched and the aggregated values over all of them.
+    /// The aggregated values are also available as the pseudo properties `Difference`, `Drift`,
+    /// `Iterations`, `MatchedNodes` and `Method` through the property accessors.
+    pub fn metrics(&self) -> Metrics {
+        let results = unsafe {
+            let results = &*self.result_ptr;
+            if results.items.is_null() {
+                &[][..]
+            } else {
+                slice::from_raw_parts(results.items, results.count as usize)
+            }
         };
 
-        if !exception.is_ok() {
-            return None;
-        }
-        let Ok(ids) = CStr::from_bytes_until_nul(&id_buf) else {
+        results
+            .iter()
+            .map(|result| ResultMetrics {
+                header: usize::try_from(result.b.uniqueHttpHeaderIndex)
+                    .ok()
+                    .and_then(|index| unsafe {
+                        dataset::unique_header_name(self.dataset(), index)
+                    }),
+                matched_user_agent: (!result.b.matchedUserAgent.is_null()).then(|| unsafe {
+                    CStr::from_ptr(result.b.matchedUserAgent)
+                        .to_string_lossy()
+                        .into_owned()
+                }),
+                method: MatchMethod::from_raw(result.method),
+                difference: result.difference,
+                drift: result.drift,
+                iterations: result.iterations,
+                matched_nodes: result.matchedNodes,
+            })
+            .collect()
+    }
+
+    /// Returns a reference to the value of the given property.
+    /// In case there is no value, e.g. because the property does not exist or the engine was
+    /// configured to ignore the requested property, the reason is returned instead.
+    pub fn get_str<P: PropertyKey>(&'b mut self, property: P) -> Result<&'b str, NoValueReason> {
+        let lookup = self.lookup(&property)?;
+        self.lookup_value(lookup)
+    }
+
+    /// Returns references to all values of the given property.
+    /// List properties, like `SetHeaderBrowserAccept-CH`, can hold more than one value,
+    /// for all other properties this is the same value `get_str` returns.
+    pub fn get_values<P: PropertyKey>(
+        &'b mut self,
+        property: P,
+    ) -> Result<impl Iterator<Item = &'b str>, NoValueReason> {
+        let lookup = self.lookup(&property)?;
+        let (items, metric) = match lookup {
+            Lookup::Property(index) => (self.value_items(index)?, None),
+            Lookup::Metric(_) => (&[][..], Some(self.lookup_value(lookup)?)),
+        };
+
+        Ok(items
+            .iter()
+            .filter_map(|item| unsafe { item_str(item) })
+            .chain(metric))
+    }
+
+    /// Returns all values of the given property joined by the separator.
+    /// Besides the properties of the data set this also supports the match metrics
+    /// of the hash engine, like `Difference`, `Drift` or `MatchedNodes`.
+    /// A property with an empty value returns an empty string, values that are not valid
+    /// UTF-8 fail with `NoValueReason::Unknown`.
+    /// See: [51Degrees Docs](https://51degrees.com/device-detection-cxx/4.4/group___fifty_one_degrees_hash.html)
+    ///
+    pub fn get_values_string<P: PropertyKey>(
+        &mut self,
+        property: P,
+        separator: &str,
+    ) -> Result<String, NoValueReason> {
+        let index = match self.lookup(&property)? {
+            Lookup::Property(index) => index,
+            lookup @ Lookup::Metric(_) => return self.lookup_value(lookup).map(str::to_string),
+        };
+
+        let c_name =
+            CString::new(property.property_name()).map_err(|_| NoValueReason::UnknownProperty)?;
+        let c_separator = CString::new(separator).map_err(|_| NoValueReason::Unknown)?;
+        let mut buf = vec![0u8; 256];
+
+        loop {
+            let mut exception = Exception::default();
+            let len = unsafe {
+                fiftyone_degrees::fiftyoneDegreesResultsHashGetValuesString(
+                    self.result_ptr,
+                    c_name.as_ptr(),
+                    buf.as_mut_ptr().cast(),
+                    buf.len(),
+                    c_separator.as_ptr(),
+                    &mut exception,
+                )
+            };
+
+            if !exception.is_ok() {
+                return Err(self.no_value_reason(index));
+            }
+            // the library returns the length needed when the buffer is too small
+            if len >= buf.len() {
+                buf.resize(len + 1, 0);
+                continue;
+            }
+            // nothing is written both for missing and for empty values
+            if len == 0 {
+                self.value_items(index)?;
+                return Ok(String::new());
+            }
+
+            buf.truncate(len);
+            return String::from_utf8(buf).map_err(|_| NoValueReason::Unknown);
+        }
+    }
+
+    /// Returns the value of a boolean property, like `IsMobile`.
+    pub fn get_bool<P: PropertyKey>(&mut self, property: P) -> Result<bool, ValueError> {
+        let lookup = self.lookup(&property)?;
+        self.expect_type(lookup, &[ValueType::Boolean])?;
+        values::parse_bool(self.lookup_value(lookup)?)
+    }
+
+    /// Returns the value of an integer property, like `ScreenPixelsWidth`.
+    pub fn get_i32<P: PropertyKey>(&mut self, property: P) -> Result<i32, ValueError> {
+        let lookup = self.lookup(&property)?;
+        self.expect_type(lookup, &[ValueType::Integer, ValueType::SingleByte])?;
+        values::parse(self.lookup_value(lookup)?, ValueType::Integer)
+    }
+
+    /// Returns the value of a floating point property, like `ScreenInchesDiagonal`.
+    /// Integer properties are converted as well.
+    pub fn get_f64<P: PropertyKey>(&mut self, property: P) -> Result<f64, ValueError> {
+        let lookup = self.lookup(&property)?;
+        self.expect_type(
+            lookup,
+            &[
+                ValueType::Double,
+                ValueType::SinglePrecisionFloat,
+                ValueType::Integer,
+                ValueType::SingleByte,
+            ],
+        )?;
+        values::parse(self.lookup_value(lookup)?, ValueType::Double)
+    }
+
+    /// Returns the value of a version property, like `BrowserVersion` or `PlatformVersion`.
+    /// Fails with a parse error for values like `Unknown`.
+    pub fn get_version<P: PropertyKey>(&mut self, property: P) -> Result<Version, ValueError> {
+        let lookup = self.lookup(&property)?;
+        self.expect_type(lookup, &[ValueType::String, ValueType::Double])?;
+        values::parse(self.lookup_value(lookup)?, ValueType::String)
+    }
+
+    /// Returns a reference to the value of a JavaScript property,
+    /// like `JavascriptGetHighEntropyValues`.
+    pub fn get_javascript<P: PropertyKey>(
+        &'b mut self,
+        property: P,
+    ) -> Result<&'b str, ValueError> {
+        let lookup = self.lookup(&property)?;
+        self.expect_type(lookup, &[ValueType::JavaScript])?;
+        Ok(self.lookup_value(lookup)?)
+    }
+
+    /// Returns the value of a standard property, converted to the value type it is
+    /// expected to have. Fails if the data set declares a different type for the property,
+    /// or the value does not parse as the expected type.
+    pub fn get_property(&mut self, property: DeviceProperty) -> Result<PropertyValue, ValueError> {
+        let expected = property.value_type();
+        let lookup = self.lookup(&property)?;
+        self.expect_type(lookup, &[expected])?;
+
+        match self.owned_value(&property)? {
+            PropertyValue::String(value) if expected != ValueType::String => {
+                Err(ValueError::Parse { value, expected })
+            }
+            value => Ok(value),
+        }
+    }
+
+    /// Aggregates the values of the `SetHeader*` properties, like `SetHeaderBrowserAccept-CH`,
+    /// into the response headers that request the client hints the engine can use.
+    /// The headers are empty if the `SetHeader*` properties are not part of the results.
+    pub fn response_headers(&mut self) -> ResponseHeaders {
+        let mut headers = ResponseHeaders::default();
+        for property in self.selected_properties() {
+            if !property.name().starts_with(response::SET_HEADER_PREFIX) {
+                continue;
+            }
+            if let Ok(value) = self.owned_value(&property) {
+                headers.add_property(property.name(), &value.to_string());
+            }
+        }
+        headers
+    }
+
+    /// Returns the JavaScript of the `JavascriptGetHighEntropyValues` property, that
+    /// requests the high entropy client hints from the browser when run in the page.
+    /// Its result is stored in the `51D_gethighentropyvalues` cookie, which is passed
+    /// to the engine with the next request.
+    pub fn get_high_entropy_values_script(&'b mut self) -> Result<&'b str, ValueError> {
+        self.get_javascript(DeviceProperty::JavascriptGetHighEntropyValues)
+    }
+
+    /// Returns handles for all properties that are part of the results, in the order of
+    /// their required property index.
+    pub fn selected_properties(&self) -> Vec<PropertyHandle> {
+        let dataset = self.dataset();
+        let count = unsafe {
+            let available = (*dataset).b.b.available;
+            if available.is_null() {
+                0
+            } else {
+                (*available).count as i32
+            }
+        };
+
+        (0..count)
+            .filter_map(|index| {
+                let name = unsafe { dataset::required_property_name(dataset, index) }?;
+                Some(PropertyHandle::new(
+                    name.to_string_lossy().into_owned(),
+                    index,
+                ))
+            })
+            .collect()
+    }
+
+    /// Copies the values of the given properties, the device ID and the match metrics
+    /// into a `DeviceInfo` that does not borrow the engine.
+    /// Properties without a value are copied with the reason why.
+    pub fn to_owned_result<I, P>(&self, properties: I) -> DeviceInfo
+    where
+        I: IntoIterator<Item = P>,
+        P: PropertyKey,
+    {
+        let values = properties
+            .into_iter()
+            .map(|property| {
+                let value = self.owned_value(&property);
+                (property.property_name().to_string(), value)
+            })
+            .collect();
+
+        DeviceInfo {
+            device_id: self.device_id(),
+            metrics: self.metrics(),
+            values,
+        }
+    }
+
+    fn expect_type(&self, lookup: Lookup, expected: &[ValueType]) -> Result<(), ValueError> {
+        let found = match lookup {
+            Lookup::Property(index) => {
+                unsafe { dataset::property_value_type(self.dataset(), index) }
+                    .ok_or(ValueError::NoValue(NoValueReason::InvalidProperty))?
+            }
+            Lookup::Metric(metric) => metric.value_type(),
+        };
+
+        if expected.contains(&found) {
+            Ok(())
+        } else {
+            Err(ValueError::TypeMismatch {
+                expected: expected[0],
+                found,
+            })
+        }
+    }
+
+    fn lookup<P: PropertyKey>(&self, property: &P) -> Result<Lookup, NoValueReason> {
+        match self.get_property_index(property) {
+            Ok(index) => Ok(Lookup::Property(index)),
+            Err(NoValueReason::UnknownProperty) => {
+                MetricProperty::from_name(property.property_name())
+                    .map(Lookup::Metric)
+                    .ok_or(NoValueReason::UnknownProperty)
+            }
+            Err(reason) => Err(reason),
+        }
+    }
+
+    fn owned_value<P: PropertyKey>(&self, property: &P) -> Result<PropertyValue, NoValueReason> {
+        let lookup = self.lookup(property)?;
+        match lookup {
+            Lookup::Property(index) => {
+                let (value_type, is_list) = unsafe {
+                    (
+                        dataset::property_value_type(self.dataset(), index),
+                        dataset::property_is_list(self.dataset(), index),
+                    )
+                };
+                // the values are copied before the next lookup can overwrite them
+                let values = unsafe { self.value_items_unchecked(index)? }
+                    .iter()
+                    .filter_map(|item| unsafe { item_str(item) })
+                    .collect();
+                Ok(PropertyValue::from_values(
+                    value_type.unwrap_or(ValueType::String),
+                    is_list.unwrap_or_default(),
+                    values,
+                ))
+            }
+            Lookup::Metric(metric) => {
+                let value = self.metric_string(metric)?;
+                Ok(PropertyValue::from_values(
+                    metric.value_type(),
+                    false,
+                    vec![value.as_str()],
+                ))
+            }
+        }
+    }
+
+    fn lookup_value(&mut self, lookup: Lookup) -> Result<&str, NoValueReason> {
+        match lookup {
+            Lookup::Property(index) => self.first_value(index),
+            Lookup::Metric(metric) => {
+                self.metric_value = self.metric_string(metric)?;
+                Ok(&self.metric_value)
+            }
+        }
+    }
+
+    fn metric_string(&self, metric: MetricProperty) -> Result<String, NoValueReason> {
+        if metric == MetricProperty::DeviceId {
+            return self.device_id().ok_or(NoValueReason::Unknown);
+        }
+
+        let metrics = self.metrics();
+        if metrics.results.is_empty() {
+            return Err(NoValueReason::NoResults);
+        }
+        match metric.integer(&metrics) {
+            Some(value) => Ok(value.to_string()),
+            None => Ok(metrics.method.to_string()),
+        }
+    }
+
+    fn device_id(&self) -> Option<String> {
+        let mut id_buf = [0u8; 512];
+        let mut exception = Exception::default();
+        unsafe {
+            fiftyoneDegreesHashGetDeviceIdFromResults(
+                self.result_ptr,
+                id_buf.as_mut_ptr() as *mut i8,
+                id_buf.len(),
+                &mut exception,
+            ) as usize
+        };
+
+        if !exception.is_ok() {
+            return None;
+        }
+        let Ok(ids) = CStr::from_bytes_until_nul(&id_buf) else {
             return None;
         };
         ids.to_str().ok().map(str::to_string)
     }
 
-    /// Returns a reference to the value of the given property.
-    /// Returns None in case the property does not exist or the engine was configured
-    /// to ignore the requested property.
-    pub fn get_str(&'b mut self, property: &'static str) -> Option<&'b str> {
-        let index = self.manager.get_property_index(property);
+    fn first_value(&mut self, index: i32) -> Result<&str, NoValueReason> {
+        self.value_items(index)?
+            .first()
+            .and_then(|item| unsafe { item_str(item) })
+            .ok_or(NoValueReason::Unknown)
+    }
+
+    fn value_items(
+        &mut self,
+        index: i32,
+    ) -> Result<&[fiftyone_degrees::fiftyoneDegreesCollectionItem], NoValueReason> {
+        unsafe { self.value_items_unchecked(index) }
+    }
+
+    /// Reads the values of a property into the value list of the results.
+    ///
+    /// # Safety
+    /// The returned items are only valid until the next call that reads values
+    /// from the results, which overwrites the value list.
+    unsafe fn value_items_unchecked(
+        &self,
+        index: i32,
+    ) -> Result<&[fiftyone_degrees::fiftyoneDegreesCollectionItem], NoValueReason> {
         let mut exception = Exception::default();
         let collection =
             unsafe { fiftyoneDegreesResultsHashGetValues(self.result_ptr, index, &mut exception) };
 
+        let (items, count) = unsafe {
+            let values = &(*self.result_ptr).values;
+            (values.items, values.count)
+        };
+        if !exception.is_ok() || collection.is_null() || items.is_null() || count == 0 {
+            return Err(self.no_value_reason(index));
+        }
+
+        Ok(unsafe { slice::from_raw_parts(items, count as usize) })
+    }
+
+    fn no_value_reason(&self, index: i32) -> NoValueReason {
+        let mut exception = Exception::default();
+        let reason = unsafe {
+            fiftyone_degrees::fiftyoneDegreesResultsHashGetNoValueReason(
+                self.result_ptr,
+                index,
+                &mut exception,
+            )
+        };
+
         if !exception.is_ok() {
-            return None;
+            return NoValueReason::Unknown;
         }
-        if collection.is_null() {
-            return None;
+        NoValueReason::from_raw(reason)
+    }
+
+    // The index has to be looked up in the data set the results were created with,
+    // the engine might have reloaded a different data set since.
+    fn get_property_index<P: PropertyKey>(&self, property: &P) -> Result<i32, NoValueReason> {
+        if let Some(index) = property.handle().and_then(|handle| {
+            handle.index_for(unsafe {
+                dataset::required_property_name(self.dataset(), handle.index())
+            })
+        }) {
+            return Ok(index);
         }
-        if unsafe { (*self.result_ptr).values.count } == 0 {
+
+        unsafe { dataset::required_property_index(self.dataset(), property.property_name()) }
+    }
+
+    fn dataset(&self) -> *mut fiftyone_degrees::fiftyoneDegreesDataSetHash {
+        unsafe { (*self.result_ptr).b.b.dataSet.cast() }
+    }
+}
+
+/// Reads the string stored in a value item of the results.
+///
+/// # Safety
+/// The item has to point to a string value that outlives the returned reference.
+unsafe fn item_str(item: &fiftyone_degrees::fiftyoneDegreesCollectionItem) -> Option<&str> {
+    unsafe {
+        let str_data = item.data.ptr as *mut fiftyone_degrees_string_t;
+        if str_data.is_null() {
             return None;
         }
-        unsafe {
-            let items = (*self.result_ptr).values.items;
-            let str_data = (*items).data.ptr as *mut fiftyone_degrees_string_t;
-            CStr::from_ptr(std::ptr::from_ref(&(*str_data).value))
-                .to_str()
-                .ok()
-        }
+        CStr::from_ptr(std::ptr::from_ref(&(*str_data).value))
+            .to_str()
+            .ok()
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::evidence::Evidence;
+    use crate::{
+        evidence::{Evidence, EvidenceKind},
+        test_support::{self, CHROME_UA},
+    };
 
     use super::*;
 
+    #[test]
+    fn thread_safety() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        fn assert_send<T: Send>() {}
+
+        assert_send_sync::<HashEngine>();
+        assert_send::<ResultsHash<'static>>();
+        assert_send_sync::<DeviceInfo>();
+    }
+
     #[test]
     fn smoke_test() {
-        let file: PathBuf =
-            "device-detection-cxx/device-detection-data/51Degrees-LiteV4.1.hash".into();
-        let manager = HashEngineBuilder::new(&file)
-            .hash_config(HashConfig::HighPerformance)
-            .init()
-            .unwrap();
-        let ua = "Mozilla/5.0 (iPhone; CPU iPhone OS 16_2 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/16.2 Mobile/15E148 Safari/604.1";
+        let manager = test_support::engine(HashConfig::HighPerformance);
 
-        let evidence = Evidence::new_with_user_agent(ua);
+        let evidence = test_support::iphone();
         let mut results = manager.process(&evidence).unwrap();
         let res = results.get_str("PlatformName");
-        assert_eq!(res, Some("iOS"));
+        assert_eq!(res, Ok("iOS"));
     }
 
     #[test]
     fn custom_properties() {
-        let file: PathBuf =
-            "device-detection-cxx/device-detection-data/51Degrees-LiteV4.1.hash".into();
-        let manager = HashEngineBuilder::new(&file)
-            .hash_config(HashConfig::HighPerformance)
+        let manager = test_support::builder(HashConfig::HighPerformance)
             .set_properties(&["IsMobile"])
             .init()
             .unwrap();
-        let ua = "Mozilla/5.0 (iPhone; CPU iPhone OS 16_2 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/16.2 Mobile/15E148 Safari/604.1";
 
-        let evidence = Evidence::new_with_user_agent(ua);
+        let evidence = test_support::iphone();
         let mut results = manager.process(&evidence).unwrap();
         let res = results.get_str("IsMobile");
-        assert_eq!(res, Some("True"));
+        assert_eq!(res, Ok("True"));
+    }
+
+    #[test]
+    fn batch_processing() {
+        let manager = test_support::engine(HashConfig::HighPerformance);
+        let evidence = vec![
+            test_support::iphone(),
+            Evidence::try_new_with_user_agent(CHROME_UA).unwrap(),
+            Evidence::default(),
+        ];
+        let properties = ["PlatformName", "IsMobile", "NotAProperty", "MatchedNodes"];
+
+        let rows: Vec<Vec<Option<String>>> = manager
+            .process_batch(&evidence, &properties)
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(rows.len(), evidence.len());
+        for (row, evidence) in rows.iter().zip(&evidence) {
+            let mut results = manager.process(evidence).unwrap();
+            assert_eq!(row[0].as_deref(), results.get_str("PlatformName").ok());
+            assert_eq!(row[1].as_deref(), results.get_str("IsMobile").ok());
+            assert_eq!(row[2], None);
+            assert_eq!(row[3].as_deref(), results.get_str("MatchedNodes").ok());
+        }
+        assert_eq!(rows[0][0].as_deref(), Some("iOS"));
+
+        #[cfg(feature = "rayon")]
+        {
+            let parallel: Vec<Vec<Option<String>>> = manager
+                .par_process_batch(&evidence, &properties)
+                .into_iter()
+                .collect::<Result<_, _>>()
+                .unwrap();
+            assert_eq!(parallel, rows);
+        }
+    }
+
+    #[test]
+    fn dynamic_properties() {
+        let properties: Vec<String> = vec!["IsMobile".into(), "PlatformName".into()];
+        let manager = test_support::builder(HashConfig::HighPerformance)
+            .set_properties(&properties)
+            .init()
+            .unwrap();
+
+        let is_mobile = manager.property("IsMobile").unwrap();
+        assert_eq!(is_mobile.name(), "IsMobile");
+        assert_eq!(
+            manager.property("BrowserName"),
+            Err(NoValueReason::PropertyNotSelected)
+        );
+
+        let evidence = test_support::iphone();
+        let mut results = manager.process(&evidence).unwrap();
+        assert_eq!(results.get_bool(&is_mobile), Ok(true));
+        assert_eq!(results.get_str(properties[1].clone()), Ok("iOS"));
+
+        manager.reload_from_original_file().unwrap();
+        let mut results = manager.process(&evidence).unwrap();
+        assert_eq!(results.get_str(&is_mobile), Ok("True"));
+    }
+
+    #[test]
+    fn no_value_reasons() {
+        let manager = test_support::builder(HashConfig::HighPerformance)
+            .set_properties(&["IsMobile"])
+            .init()
+            .unwrap();
+
+        let evidence = test_support::iphone();
+        let mut results = manager.process(&evidence).unwrap();
+        assert_eq!(
+            results.get_str("PlatformName"),
+            Err(NoValueReason::PropertyNotSelected)
+        );
+        assert_eq!(
+            results.get_str("NotAProperty"),
+            Err(NoValueReason::UnknownProperty)
+        );
+        assert_eq!(
+            results.get_bool("NotAProperty"),
+            Err(ValueError::NoValue(NoValueReason::UnknownProperty))
+        );
+
+        let mut results = manager.process(&Evidence::default()).unwrap();
+        let reason = results.get_str("IsMobile").unwrap_err();
+        assert!(!reason.to_string().is_empty());
+    }
+
+    #[test]
+    fn list_values() {
+        let manager = test_support::engine(HashConfig::HighPerformance);
+
+        let evidence = Evidence::try_new_with_user_agent(CHROME_UA).unwrap();
+        let mut results = manager.process(&evidence).unwrap();
+
+        let values = results
+            .get_values("SetHeaderBrowserAccept-CH")
+            .ok()
+            .map(|values| values.collect::<Vec<&str>>().join("|"));
+        let joined = results.get_values_string("SetHeaderBrowserAccept-CH", "|");
+        assert_eq!(joined.ok(), values);
+
+        let values: Vec<&str> = results.get_values("PlatformName").unwrap().collect();
+        assert_eq!(values, vec!["Windows"]);
+        assert_eq!(
+            results.get_values_string("PlatformName", ","),
+            Ok("Windows".to_string())
+        );
+        assert!(matches!(
+            results.get_values("NotAProperty"),
+            Err(NoValueReason::UnknownProperty)
+        ));
+        assert_eq!(
+            results.get_values_string("NotAProperty", ","),
+            Err(NoValueReason::UnknownProperty)
+        );
+
+        let matched_nodes = results.get_values_string("MatchedNodes", ",").unwrap();
+        assert_eq!(matched_nodes, results.metrics().matched_nodes.to_string());
+    }
+
+    #[test]
+    fn match_metrics() {
+        let manager = test_support::engine(
+            HashConfigBuilder::new(HashConfig::HighPerformance).update_matched_user_agent(true),
+        );
+
+        let evidence = test_support::iphone();
+        let mut results = manager.process(&evidence).unwrap();
+        let metrics = results.metrics();
+
+        assert_eq!(metrics.results.len(), 1);
+        assert!(metrics.matched_nodes > 0);
+        assert_ne!(metrics.method, MatchMethod::None);
+        assert!(metrics.results[0].matched_user_agent.is_some());
+        assert_eq!(
+            metrics.results[0].header.as_deref().map(str::to_lowercase),
+            Some("user-agent".to_string())
+        );
+
+        assert_eq!(results.get_i32("Difference"), Ok(metrics.difference));
+        assert_eq!(results.get_i32("MatchedNodes"), Ok(metrics.matched_nodes));
+        assert_eq!(
+            results.get_str("Method"),
+            Ok(metrics.method.to_string().as_str())
+        );
+        assert!(matches!(
+            results.get_bool("Drift"),
+            Err(ValueError::TypeMismatch {
+                found: ValueType::Integer,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn typed_values() {
+        let manager = test_support::engine(HashConfig::HighPerformance);
+
+        let evidence = test_support::iphone();
+        let mut results = manager.process(&evidence).unwrap();
+        assert_eq!(results.get_bool("IsMobile"), Ok(true));
+        assert_eq!(
+            results
+                .get_version("PlatformVersion")
+                .map(|v| (v.major, v.minor)),
+            Ok((16, Some(2)))
+        );
+        assert!(matches!(
+            results.get_i32("IsMobile"),
+            Err(ValueError::TypeMismatch {
+                found: ValueType::Boolean,
+                ..
+            })
+        ));
+        assert!(matches!(
+            results.get_bool("PlatformName"),
+            Err(ValueError::TypeMismatch {
+                expected: ValueType::Boolean,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn adjusted_config() {
+        let config = HashConfigBuilder::new(HashConfig::LowMemory)
+            .difference(10)
+            .drift(2)
+            .concurrency(4)
+            .update_matched_user_agent(true)
+            .collection(
+                DataCollection::Strings,
+                CollectionConfig {
+                    capacity: Some(1000),
+                    ..Default::default()
+                },
+            )
+            .collection(
+                DataCollection::Profiles,
+                CollectionConfig {
+                    loaded: Some(0),
+                    capacity: Some(100),
+                    concurrency: Some(2),
+                },
+            );
+        assert_eq!(config.validate(), Ok(()));
+        let manager = test_support::engine(config);
+        // the profiles use fewer handles than the other collections
+        assert_eq!(manager.concurrency(), 4);
+
+        let evidence = test_support::iphone();
+        let mut results = manager.process(&evidence).unwrap();
+        assert_eq!(results.get_str("PlatformName"), Ok("iOS"));
+    }
+
+    #[test]
+    fn balanced_temp_config() {
+        let config = HashConfigBuilder::new(HashConfig::BalancedTemp)
+            .temp_dirs(&[std::env::temp_dir()])
+            .reuse_temp_file(false);
+        let manager = test_support::engine(config);
+
+        let evidence = test_support::iphone();
+        let mut results = manager.process(&evidence).unwrap();
+        assert_eq!(results.get_str("PlatformName"), Ok("iOS"));
+
+        manager.reload_from_original_file().unwrap();
+        let mut results = manager.process(&evidence).unwrap();
+        assert_eq!(results.get_str("PlatformName"), Ok("iOS"));
+    }
+
+    #[test]
+    fn invalid_config() {
+        let config = HashConfigBuilder::new(HashConfig::HighPerformance)
+            .use_performance_graph(false)
+            .use_predictive_graph(false);
+        assert_eq!(config.validate(), Err(HashConfigError::NoGraph));
+
+        let config = HashConfigBuilder::new(HashConfig::HighPerformance).drift(-1);
+        assert_eq!(config.validate(), Err(HashConfigError::NegativeDrift(-1)));
+
+        let config = HashConfigBuilder::new(HashConfig::LowMemory).concurrency(0);
+        assert_eq!(config.validate(), Err(HashConfigError::ZeroConcurrency));
+
+        let config = HashConfigBuilder::new(HashConfig::LowMemory).collection(
+            DataCollection::Values,
+            CollectionConfig {
+                concurrency: Some(0),
+                ..Default::default()
+            },
+        );
+        assert_eq!(config.validate(), Err(HashConfigError::ZeroConcurrency));
+    }
+
+    #[test]
+    fn reload_keeps_existing_results() {
+        let manager = test_support::engine(HashConfig::InMemory);
+
+        let evidence = test_support::iphone();
+        let mut before = manager.process(&evidence).unwrap();
+
+        manager.reload_from_original_file().unwrap();
+        manager
+            .reload_from_file(&test_support::data_file())
+            .unwrap();
+
+        let mut after = manager.process(&evidence).unwrap();
+        assert_eq!(before.get_str("PlatformName"), Ok("iOS"));
+        assert_eq!(after.get_str("PlatformName"), Ok("iOS"));
+    }
+
+    #[test]
+    fn failed_reload() {
+        let manager = test_support::engine(HashConfig::InMemory);
+
+        let error = manager
+            .reload_from_file(Path::new("does-not-exist.hash"))
+            .unwrap_err();
+        assert!(matches!(
+            error.kind,
+            HashManagerErrorKind::Reload(_) | HashManagerErrorKind::ReloadWithoutException(_)
+        ));
+        assert_eq!(error.to_string(), "error reloading the data set.");
+
+        // the engine keeps using the data set it had before
+        let evidence = test_support::iphone();
+        let mut results = manager.process(&evidence).unwrap();
+        assert_eq!(results.get_str("PlatformName"), Ok("iOS"));
+    }
+
+    #[test]
+    fn reused_results() {
+        let manager = test_support::engine(HashConfig::InMemory);
+        let iphone = test_support::iphone();
+        let windows = test_support::evidence(&[
+            (EvidenceKind::HeaderString, "user-agent", CHROME_UA),
+            (
+                EvidenceKind::HeaderString,
+                "sec-ch-ua-platform",
+                "\"Windows\"",
+            ),
+        ]);
+
+        let mut results = manager.create_results(1).unwrap();
+        manager.process_into(&mut results, &iphone).unwrap();
+        assert_eq!(results.get_str("PlatformName"), Ok("iOS"));
+
+        // the second evidence exceeds the capacity of the results
+        manager.process_into(&mut results, &windows).unwrap();
+        assert_eq!(results.get_str("PlatformName"), Ok("Windows"));
+        assert_eq!(results.capacity, 2);
+
+        manager.reload_from_original_file().unwrap();
+        manager.process_into(&mut results, &iphone).unwrap();
+        assert_eq!(results.get_str("PlatformName"), Ok("iOS"));
+        assert_eq!(results.dataset(), manager.dataset().as_ptr());
+    }
+
+    #[test]
+    fn owned_result() {
+        let manager = test_support::engine(HashConfig::InMemory);
+
+        let evidence = test_support::iphone();
+        let mut results = manager.process(&evidence).unwrap();
+        let device_id = results.get_device_id();
+        // copying only needs a shared reference to the results
+        let shared = &results;
+        let info = shared.to_owned_result([
+            "PlatformName",
+            "IsMobile",
+            "HardwareName",
+            "NotAProperty",
+            "MatchedNodes",
+        ]);
+        let all = shared.to_owned_result(shared.selected_properties());
+        assert_eq!(
+            all.iter().count(),
+            manager.properties().iter().filter(|p| p.selected).count()
+        );
+        assert_eq!(all.get("HardwareName"), info.get("HardwareName"));
+        drop(results);
+        manager.reload_from_original_file().unwrap();
+
+        assert_eq!(info.device_id, device_id);
+        assert_eq!(info.get_str("PlatformName"), Some("iOS"));
+        assert_eq!(info.get_bool("IsMobile"), Some(true));
+        assert!(matches!(
+            info.get("HardwareName"),
+            Ok(PropertyValue::List(names)) if !names.is_empty()
+        ));
+        assert_eq!(
+            info.get("NotAProperty"),
+            Err(NoValueReason::UnknownProperty)
+        );
+        assert_eq!(
+            info.get_i32("MatchedNodes"),
+            Some(info.metrics.matched_nodes)
+        );
+        assert_eq!(
+            info.get("ScreenPixelsWidth"),
+            Err(NoValueReason::UnknownProperty)
+        );
+
+        #[cfg(feature = "serde")]
+        {
+            let serialized = serde_yaml::to_string(&info).unwrap();
+            let deserialized: DeviceInfo = serde_yaml::from_str(&serialized).unwrap();
+            assert_eq!(deserialized, info);
+        }
+    }
+
+    #[test]
+    fn filtered_evidence() {
+        let manager = test_support::engine(HashConfig::HighPerformance);
+        let keys = manager.evidence_keys();
+        assert!(keys.headers().iter().any(|header| header == "user-agent"));
+        assert!(keys.contains(EvidenceKind::Query, "51D_deviceId"));
+        assert!(!keys.contains(EvidenceKind::Cookie, "session"));
+
+        let evidence = || {
+            test_support::evidence(&[
+                (EvidenceKind::HeaderString, "User-Agent", CHROME_UA),
+                (EvidenceKind::HeaderString, "sec-ch-ua-mobile", "?0"),
+                (
+                    EvidenceKind::HeaderString,
+                    "sec-ch-ua-platform",
+                    "\"Windows\"",
+                ),
+                (EvidenceKind::HeaderString, "accept-language", "en-US"),
+                (EvidenceKind::HeaderString, "x-request-id", "1234"),
+                (EvidenceKind::Cookie, "session", "abc"),
+                (EvidenceKind::Server, "client-ip", "127.0.0.1"),
+            ])
+        };
+        let filtered = evidence().retain_keys(&keys);
+        assert!(filtered.len() < evidence().len());
+        assert!(filtered.len() >= 1);
+
+        let mut all = manager.process(&evidence()).unwrap();
+        let mut retained = manager.process(&filtered).unwrap();
+        assert_eq!(all.get_device_id(), retained.get_device_id());
+        assert_eq!(all.metrics(), retained.metrics());
+    }
+
+    #[test]
+    fn response_headers() {
+        let manager = test_support::engine(HashConfig::HighPerformance);
+        let available = |name: &str| {
+            manager
+                .properties()
+                .iter()
+                .any(|property| property.name.starts_with(name) && property.available)
+        };
+
+        let evidence = Evidence::try_new_with_user_agent(CHROME_UA).unwrap();
+        let mut results = manager.process(&evidence).unwrap();
+        let headers = results.response_headers();
+        if available("SetHeader") {
+            assert!(
+                headers
+                    .accept_ch()
+                    .iter()
+                    .any(|hint| hint.eq_ignore_ascii_case("Sec-CH-UA-Full-Version-List"))
+            );
+        } else {
+            assert!(headers.is_empty());
+        }
+
+        if available("JavascriptGetHighEntropyValues") {
+            let script = results.get_high_entropy_values_script().unwrap();
+            assert!(script.contains("getHighEntropyValues"));
+        }
+    }
+
+    #[test]
+    fn from_memory() {
+        let hash_data = std::fs::read(test_support::DATA_FILE).unwrap();
+        let manager = HashEngineBuilder::from_memory(hash_data.clone())
+            .hash_config(HashConfig::InMemory)
+            .init()
+            .unwrap();
+
+        let evidence = test_support::iphone();
+        let mut before = manager.process(&evidence).unwrap();
+
+        manager.reload_from_memory(hash_data).unwrap();
+
+        let mut after = manager.process(&evidence).unwrap();
+        assert_eq!(before.get_str("PlatformName"), Ok("iOS"));
+        assert_eq!(after.get_str("PlatformName"), Ok("iOS"));
+    }
+
+    #[test]
+    fn in_memory_after_reload() {
+        let hash_data = std::fs::read(test_support::DATA_FILE).unwrap();
+        let manager = HashEngineBuilder::from_memory(hash_data.clone())
+            .hash_config(HashConfig::LowMemory)
+            .init()
+            .unwrap();
+        assert!(manager.is_in_memory());
+
+        manager
+            .reload_from_file(&test_support::data_file())
+            .unwrap();
+        assert!(!manager.is_in_memory());
+
+        manager.reload_from_memory(hash_data).unwrap();
+        assert!(manager.is_in_memory());
+
+        let manager = test_support::engine(HashConfig::InMemory);
+        manager.reload_from_original_file().unwrap();
+        assert!(manager.is_in_memory());
+    }
+
+    #[test]
+    fn reload_frees_buffers() {
+        struct Tracked(Vec<u8>, Arc<std::sync::atomic::AtomicUsize>);
+
+        impl AsRef<[u8]> for Tracked {
+            fn as_ref(&self) -> &[u8] {
+                &self.0
+            }
+        }
+
+        impl Drop for Tracked {
+            fn drop(&mut self) {
+                self.1.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            }
+        }
+
+        let file = test_support::data_file();
+        let hash_data = std::fs::read(&file).unwrap();
+        let freed = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let freed_count = || freed.load(std::sync::atomic::Ordering::SeqCst);
+
+        let manager =
+            HashEngineBuilder::from_memory(Tracked(hash_data.clone(), Arc::clone(&freed)))
+                .hash_config(HashConfig::InMemory)
+                .init()
+                .unwrap();
+        let evidence = test_support::iphone();
+        let mut first = manager.process(&evidence).unwrap();
+
+        for reload in 1..=3 {
+            manager
+                .reload_from_memory(Tracked(hash_data.clone(), Arc::clone(&freed)))
+                .unwrap();
+            // the first buffer is still used by the results, the others are freed
+            assert_eq!(freed_count(), reload - 1);
+        }
+
+        assert_eq!(first.get_str("PlatformName"), Ok("iOS"));
+        drop(first);
+        assert_eq!(freed_count(), 3);
+
+        manager.reload_from_file(&file).unwrap();
+        assert_eq!(freed_count(), 4);
+        let mut results = manager.process(&evidence).unwrap();
+        assert_eq!(results.get_str("PlatformName"), Ok("iOS"));
+
+        // the buffer of a failed reload is not kept either
+        let error = manager
+            .reload_from_memory(Tracked(vec![0; 64], Arc::clone(&freed)))
+            .unwrap_err();
+        assert_eq!(error.to_string(), "error reloading the data set.");
+        assert_eq!(freed_count(), 5);
+    }
+
+    #[test]
+    fn process_during_reload() {
+        let hash_data = std::fs::read(test_support::DATA_FILE).unwrap();
+        let manager = HashEngineBuilder::from_memory(hash_data.clone())
+            .hash_config(HashConfig::InMemory)
+            .init()
+            .unwrap();
+        let done = std::sync::atomic::AtomicBool::new(false);
+
+        std::thread::scope(|scope| {
+            // processes until the reloads are done, at least once after the last one
+            scope.spawn(|| {
+                let evidence = test_support::iphone();
+                loop {
+                    let finished = done.load(std::sync::atomic::Ordering::Acquire);
+                    let mut results = manager.process(&evidence).unwrap();
+                    assert_eq!(results.get_str("PlatformName"), Ok("iOS"));
+                    if finished {
+                        break;
+                    }
+                }
+            });
+
+            for _ in 0..3 {
+                manager.reload_from_memory(hash_data.clone()).unwrap();
+                manager
+                    .reload_from_file(&test_support::data_file())
+                    .unwrap();
+            }
+            done.store(true, std::sync::atomic::Ordering::Release);
+        });
     }
 }
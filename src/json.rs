@@ -0,0 +1,167 @@
+use serde::{Serialize, Serializer, ser::SerializeMap};
+
+use crate::device_info::{DeviceInfo, PropertyValue};
+
+/// Serializes a `DeviceInfo` in the shape of the JSON produced by the 51Degrees
+/// cloud and on-premise pipelines, e.g.
+/// `{"device":{"ismobile":true,"hardwarename":["iPhone"],"screenpixelswidth":null}}`.
+///
+/// Property names are lower case, list properties are arrays and properties without
+/// a value are `null`. With `include_null_reasons` every `null` is followed by a
+/// `<name>nullreason` entry with the message of the reason.
+#[derive(Clone, Copy, Debug)]
+pub struct DeviceJson<'a> {
+    info: &'a DeviceInfo,
+    null_reasons: bool,
+}
+
+impl DeviceInfo {
+    /// Returns a view of the device information that serializes to the 51Degrees JSON format.
+    /// Use `ResultsHash::selected_properties` to copy all properties of the results.
+    pub fn json(&self) -> DeviceJson<'_> {
+        DeviceJson {
+            info: self,
+            null_reasons: false,
+        }
+    }
+}
+
+impl DeviceJson<'_> {
+    /// Adds a `<name>nullreason` entry for every property without a value.
+    pub fn include_null_reasons(mut self, include: bool) -> Self {
+        self.null_reasons = include;
+        self
+    }
+}
+
+impl Serialize for DeviceJson<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(1))?;
+        map.serialize_entry("device", &DeviceProperties(*self))?;
+        map.end()
+    }
+}
+
+struct DeviceProperties<'a>(DeviceJson<'a>);
+
+impl Serialize for DeviceProperties<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let DeviceJson { info, null_reasons } = self.0;
+        let mut map = serializer.serialize_map(None)?;
+
+        for (name, value) in info.iter() {
+            // the metrics are written below, even if they were not requested
+            if is_metric(name) {
+                continue;
+            }
+
+            let name = name.to_lowercase();
+            match value {
+                Ok(value) => map.serialize_entry(&name, &JsonValue(value))?,
+                Err(reason) => {
+                    map.serialize_entry(&name, &())?;
+                    if null_reasons {
+                        map.serialize_entry(&format!("{}nullreason", name), &reason.to_string())?;
+                    }
+                }
+            }
+        }
+
+        map.serialize_entry("deviceid", &info.device_id)?;
+        map.serialize_entry("difference", &info.metrics.difference)?;
+        map.serialize_entry("drift", &info.metrics.drift)?;
+        map.serialize_entry("iterations", &info.metrics.iterations)?;
+        map.serialize_entry("matchednodes", &info.metrics.matched_nodes)?;
+        map.serialize_entry("method", &info.metrics.method.to_string())?;
+        map.end()
+    }
+}
+
+fn is_metric(name: &str) -> bool {
+    [
+        "deviceid",
+        "difference",
+        "drift",
+        "iterations",
+        "matchednodes",
+        "method",
+    ]
+    .iter()
+    .any(|metric| name.eq_ignore_ascii_case(metric))
+}
+
+struct JsonValue<'a>(&'a PropertyValue);
+
+impl Serialize for JsonValue<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.0 {
+            PropertyValue::String(value) | PropertyValue::JavaScript(value) => {
+                serializer.serialize_str(value)
+            }
+            PropertyValue::Integer(value) => serializer.serialize_i32(*value),
+            PropertyValue::Double(value) => serializer.serialize_f64(*value),
+            PropertyValue::Boolean(value) => serializer.serialize_bool(*value),
+            PropertyValue::List(values) => values.serialize(serializer),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        metrics::{MatchMethod, Metrics},
+        values::NoValueReason,
+    };
+
+    use super::*;
+
+    #[test]
+    fn device_json() {
+        let info = DeviceInfo {
+            device_id: Some("12280-0-0-0".to_string()),
+            metrics: Metrics {
+                method: MatchMethod::Performance,
+                matched_nodes: 1,
+                ..Metrics::default()
+            },
+            values: [
+                ("IsMobile", Ok(PropertyValue::Boolean(true))),
+                (
+                    "HardwareName",
+                    Ok(PropertyValue::List(vec!["iPhone".to_string()])),
+                ),
+                ("ScreenPixelsWidth", Ok(PropertyValue::Integer(1170))),
+                ("PlatformName", Err(NoValueReason::NoResults)),
+                ("MatchedNodes", Ok(PropertyValue::Integer(1))),
+            ]
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect(),
+        };
+
+        let json = serde_json::to_value(info.json()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "device": {
+                    "hardwarename": ["iPhone"],
+                    "ismobile": true,
+                    "platformname": null,
+                    "screenpixelswidth": 1170,
+                    "deviceid": "12280-0-0-0",
+                    "difference": 0,
+                    "drift": 0,
+                    "iterations": 0,
+                    "matchednodes": 1,
+                    "method": "PERFORMANCE",
+                }
+            })
+        );
+
+        let json = serde_json::to_value(info.json().include_null_reasons(true)).unwrap();
+        assert_eq!(
+            json["device"]["platformnamenullreason"],
+            NoValueReason::NoResults.to_string()
+        );
+    }
+}
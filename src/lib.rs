@@ -16,45 +16,119 @@
 //!     .init()
 //!     .unwrap();
 //!
-//! let evidence = Evidence::new_with_user_agent(
+//! let evidence = Evidence::try_new_with_user_agent(
 //! "Mozilla/5.0 (iPhone; CPU iPhone OS 16_2 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/16.2 Mobile/15E148 Safari/604.1"
-//! );
+//! )
+//! .unwrap();
 //!
 //! let mut results = manager.process(&evidence).unwrap();
 //! let res = results.get_str("PlatformName");
 //!
-//! assert_eq!(res, Some("iOS"));
+//! assert_eq!(res, Ok("iOS"));
 //! ```
+//! ## Evidence from HTTP requests
+//! With the `http` feature `Evidence::from_headers` and `Evidence::from_request` build the
+//! evidence from the headers, cookies and query string of an `http::Request`,
+//! `Evidence::from_request_with_addr` adds the remote address the server accepted it from.
+//! Values that can not be passed to the engine are skipped instead of panicking.
+//! `Evidence::retain_keys` drops the evidence the engine does not use, with the keys returned
+//! by `HashEngine::evidence_keys`.
+//! `Evidence::try_add` and `Evidence::try_new_with_user_agent` return an `EvidenceError` for
+//! NUL characters, the panicking `Evidence::add` and `Evidence::new_with_user_agent` are
+//! deprecated. An `EvidencePolicy` can strip or truncate NUL characters and cap the length of values.
+//! `ClientHints` reads and validates the User-Agent Client Hints headers, or with the `serde`
+//! feature the payload of `getHighEntropyValues()`, and adds them with `Evidence::add_client_hints`.
+//! To request the client hints from the browser, `ResultsHash::response_headers` returns the
+//! `Accept-CH` headers for the response and `ResultsHash::get_high_entropy_values_script` the
+//! JavaScript that sends the high entropy values with the next request.
+//! With the `tower` feature the `DeviceDetectionLayer` runs the detection for every request of
+//! a tower service, like an axum router, and inserts the `DeviceInfo` into the request extensions.
+//! With the `axum` and `actix-web` features `Device` and `DeviceInfo` can be taken as handler
+//! arguments, detected with the `Arc<HashEngine>` of the axum state or the `Data<HashEngine>`
+//! of the actix app. They detect synchronously, which suits engines with the whole data set
+//! in memory.
+//! With the `tokio` feature the `AsyncHashEngine` processes evidence from async code. Engines
+//! that read from the data file run on the blocking pool with a limit of concurrent detections,
+//! engines with the whole data set in memory run inline.
+//!
 //! ## Configuration
 //! Currently switching between hash configs and defining a list of result properties is
 //! implemented.
 //! Peformance can be switched between:
 //! - HighPerformance
 //! - InMemory
+//! - SingleLoaded
 //! - Balanced
+//! - BalancedTemp
 //! - LowMemory
 //!
 //! With the first config being the fastest at the expense of memory footprint and
 //! the last being the opposite.
 //!
-//! Properties are a list of static strings that define the values that are put into the result
+//! Single values of a preset, like the allowed difference and drift or the cache sizes
+//! of the data collections, can be adjusted with the `HashConfigBuilder`.
+//! `BalancedTemp` reads from a temporary copy of the data file, so the file can be replaced
+//! on disk and reloaded while the engine is running.
+//!
+//! Properties are a list of property names that define the values that are put into the result
 //! during the processing of the evidence.
 //! Limiting the device properties in the result can help speed up the processing of the evidence.
+//! `HashEngine::property` resolves a property name into a `PropertyHandle` once, so reading its
+//! values from the results skips the lookup by name.
+//!
+//! The standard properties of the 51Degrees data sets are available as `DeviceProperty`, which
+//! can be used in place of property names and knows the value type of the property.
+//! `Device::from_results` reads the common properties into typed fields in one call.
+//!
+//! `ResultsHash::to_owned_result` copies the typed values of properties, the device ID and the
+//! match metrics into a `DeviceInfo`, which does not borrow the engine and can be cached or
+//! sent to other threads. With the `serde` feature it can be serialized as well, and
+//! `DeviceInfo::json` serializes it in the JSON format of the 51Degrees cloud and on-premise
+//! pipelines, e.g. with all properties of `ResultsHash::selected_properties`.
+//!
+//! ## Concurrency
+//! The `HashEngine` is `Send` and `Sync`, a single engine can be shared between threads,
+//! e.g. in an `Arc`, and process evidence concurrently. Engines that read from the data file
+//! share `HashEngine::concurrency` file handles, detections beyond that fail instead of waiting.
+//!
+//! `HashEngine::process_batch` processes a slice of evidence and extracts the values of the
+//! given properties into owned rows, reusing a single result for the whole batch.
+//! With the `rayon` feature `HashEngine::par_process_batch` spreads the batch over the rayon
+//! thread pool.
+//!
+//! Results allocated once with `HashEngine::create_results` can be refilled with
+//! `HashEngine::process_into`, which avoids allocating new results for every evidence.
 
+#[cfg(feature = "tokio")]
+pub mod async_engine;
+pub mod dataset;
+pub mod device;
+pub mod device_info;
 pub mod evidence;
+#[cfg(any(feature = "axum", feature = "actix-web"))]
+pub mod extract;
 mod fiftyone_degrees;
 pub mod hash_engine;
+#[cfg(feature = "serde")]
+pub mod json;
+pub mod metrics;
+#[cfg(feature = "tower")]
+pub mod middleware;
+pub mod property;
+pub mod response;
+#[cfg(test)]
+mod test_support;
+pub mod values;
 
 #[cfg(test)]
 mod tests {
-    use std::{collections::HashMap, fs, path::PathBuf, str::FromStr};
+    use std::{collections::HashMap, fs, path::PathBuf, str::FromStr, sync::Arc, thread};
 
     use crate::evidence::Evidence;
 
     use super::*;
 
-    #[test]
-    fn full_test() {
+    fn load_cases() -> Option<Vec<Evidence>> {
         let test_file = PathBuf::from_str(
             "device-detection-cxx/device-detection-data/20000 Evidence Records.yml",
         )
@@ -62,7 +136,7 @@ mod tests {
 
         let Ok(test_data) = fs::read_to_string(&test_file) else {
             // TODO: find a better way to split tests into fast and slow
-            return;
+            return None;
         };
 
         let mut cases: Vec<HashMap<String, String>> = Vec::default();
@@ -81,12 +155,23 @@ mod tests {
                     let field = key
                         .strip_prefix("header.")
                         .expect("all hints should be headers");
-                    evidence = evidence.add(evidence::EvidenceKind::HeaderString, field, value)
+                    evidence = evidence
+                        .try_add(evidence::EvidenceKind::HeaderString, field, value)
+                        .expect("the records to contain no NUL characters")
                 }
                 evidence
             })
             .collect();
 
+        Some(cases_evidence)
+    }
+
+    #[test]
+    fn full_test() {
+        let Some(cases_evidence) = load_cases() else {
+            return;
+        };
+
         let hash_engine = hash_engine::HashEngineBuilder::new(
             &PathBuf::from_str(
                 "device-detection-cxx/device-detection-data/51Degrees-LiteV4.1.hash",
@@ -106,4 +191,120 @@ mod tests {
             assert!(result.get_device_id().is_some_and(|id| id.len() > 0))
         }
     }
+
+    /// Processes the evidence on `threads` threads at the same time and compares the
+    /// device IDs with the ones of a single thread. With `tolerate_busy`, detections may
+    /// fail because the engine ran out of file handles.
+    fn process_threaded(
+        hash_engine: Arc<hash_engine::HashEngine>,
+        cases_evidence: Vec<Evidence>,
+        threads: usize,
+        tolerate_busy: bool,
+    ) {
+        let expected: Vec<Option<String>> = cases_evidence
+            .iter()
+            .map(|evidence| {
+                hash_engine
+                    .process(evidence)
+                    .expect("processing evidence to work")
+                    .get_device_id()
+            })
+            .collect();
+
+        let chunk_size = cases_evidence.len().div_ceil(threads);
+        let cases_evidence = Arc::new(cases_evidence);
+
+        let handles: Vec<_> = (0..threads)
+            .map(|worker| {
+                let hash_engine = Arc::clone(&hash_engine);
+                let cases_evidence = Arc::clone(&cases_evidence);
+                thread::spawn(move || {
+                    // every thread walks all records, starting at a different offset
+                    let offset = worker * chunk_size;
+                    let mut ids = vec![None; cases_evidence.len()];
+                    for i in (0..cases_evidence.len()).map(|i| (i + offset) % cases_evidence.len())
+                    {
+                        match hash_engine.process(&cases_evidence[i]) {
+                            Ok(mut result) => ids[i] = Some(result.get_device_id()),
+                            Err(e) if tolerate_busy => {
+                                assert_eq!(e.to_string(), "error proccessing the evidence")
+                            }
+                            Err(e) => panic!("processing evidence to work: {e}"),
+                        }
+                    }
+                    ids
+                })
+            })
+            .collect();
+
+        let mut processed = 0;
+        for handle in handles {
+            let ids = handle.join().expect("thread to finish");
+            for (id, expected) in ids.into_iter().zip(&expected) {
+                let Some(id) = id else {
+                    continue;
+                };
+                // reading the device ID can run out of file handles as well
+                if id.is_some() || !tolerate_busy {
+                    assert_eq!(&id, expected);
+                    processed += 1;
+                }
+            }
+        }
+        assert!(processed > 0);
+        if !tolerate_busy {
+            assert_eq!(processed, threads * expected.len());
+        }
+    }
+
+    #[test]
+    fn multi_threaded_test() {
+        let Some(cases_evidence) = load_cases() else {
+            return;
+        };
+
+        let hash_engine = Arc::new(test_support::engine(hash_engine::HashConfig::HighPerformance));
+        process_threaded(hash_engine, cases_evidence, 16, false);
+    }
+
+    #[test]
+    fn multi_threaded_file_test() {
+        // more threads than file handles, detections that find none fail instead of waiting
+        for config in [
+            hash_engine::HashConfig::LowMemory,
+            hash_engine::HashConfig::Balanced,
+        ] {
+            let Some(mut cases_evidence) = load_cases() else {
+                return;
+            };
+            cases_evidence.truncate(2000);
+
+            let config = hash_engine::HashConfigBuilder::new(config).concurrency(2);
+            let hash_engine = Arc::new(test_support::engine(config));
+            assert_eq!(hash_engine.concurrency(), 2);
+            process_threaded(hash_engine, cases_evidence, 8, true);
+        }
+    }
+
+    #[test]
+    fn filtered_evidence_test() {
+        let Some(cases_evidence) = load_cases() else {
+            return;
+        };
+
+        let hash_engine = test_support::engine(hash_engine::HashConfig::HighPerformance);
+        let keys = hash_engine.evidence_keys();
+
+        for evidence in cases_evidence {
+            let expected = hash_engine
+                .process(&evidence)
+                .expect("processing evidence to work")
+                .get_device_id();
+            let filtered = evidence.retain_keys(&keys);
+            let mut result = hash_engine
+                .process(&filtered)
+                .expect("processing evidence to work");
+            assert_eq!(result.get_device_id(), expected);
+        }
+    }
 }
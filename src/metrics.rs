@@ -0,0 +1,188 @@
+use std::fmt::Display;
+
+use crate::{
+    fiftyone_degrees::{
+        self,
+        e_fiftyone_degrees_hash_match_method_FIFTYONE_DEGREES_HASH_MATCH_METHOD_COMBINED as METHOD_COMBINED,
+        e_fiftyone_degrees_hash_match_method_FIFTYONE_DEGREES_HASH_MATCH_METHOD_PERFORMANCE as METHOD_PERFORMANCE,
+        e_fiftyone_degrees_hash_match_method_FIFTYONE_DEGREES_HASH_MATCH_METHOD_PREDICTIVE as METHOD_PREDICTIVE,
+    },
+    values::ValueType,
+};
+
+/// The graph the hash engine used to find a match.
+/// Ordered like the methods of the library: no match, then the methods that match from the
+/// most to the least exact, so the greater of two matched methods is the less exact one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MatchMethod {
+    /// No match was found.
+    #[default]
+    None,
+    /// Matched with the performance optimized graph.
+    Performance,
+    /// Matched with the performance graph after the predictive graph failed or vice versa.
+    Combined,
+    /// Matched with the predictive optimized graph.
+    Predictive,
+}
+
+impl MatchMethod {
+    pub(crate) fn from_raw(method: fiftyone_degrees::fiftyoneDegreesHashMatchMethod) -> Self {
+        match method {
+            METHOD_PERFORMANCE => Self::Performance,
+            METHOD_COMBINED => Self::Combined,
+            METHOD_PREDICTIVE => Self::Predictive,
+            _ => Self::None,
+        }
+    }
+}
+
+impl Display for MatchMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MatchMethod::None => f.write_str("NONE"),
+            MatchMethod::Performance => f.write_str("PERFORMANCE"),
+            MatchMethod::Combined => f.write_str("COMBINED"),
+            MatchMethod::Predictive => f.write_str("PREDICTIVE"),
+        }
+    }
+}
+
+/// The metrics of a single hash result, one per evidence header the engine matched.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ResultMetrics {
+    /// The name of the evidence header the result was matched with.
+    pub header: Option<String>,
+    /// The characters of the evidence that were matched, only available if
+    /// `HashConfigBuilder::update_matched_user_agent` is enabled.
+    pub matched_user_agent: Option<String>,
+    pub method: MatchMethod,
+    /// The difference between the hashes of the evidence and the matched nodes.
+    pub difference: i32,
+    /// The maximum drift of a matched hash from its expected position.
+    pub drift: i32,
+    /// The number of iterations needed to find the match.
+    pub iterations: i32,
+    /// The number of hash nodes matched.
+    pub matched_nodes: i32,
+}
+
+/// The metrics of a detection, aggregated over all results the same way the library
+/// reports its metric pseudo properties.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Metrics {
+    pub results: Vec<ResultMetrics>,
+    /// The greatest method of all results, which is the least exact method any of them
+    /// matched with. `MatchMethod::None` only if none of the results matched.
+    pub method: MatchMethod,
+    /// The sum of the differences of all results.
+    pub difference: i32,
+    /// The maximum drift of all results.
+    pub drift: i32,
+    /// The sum of the iterations of all results.
+    pub iterations: i32,
+    /// The sum of the matched nodes of all results.
+    pub matched_nodes: i32,
+}
+
+impl FromIterator<ResultMetrics> for Metrics {
+    fn from_iter<T: IntoIterator<Item = ResultMetrics>>(iter: T) -> Self {
+        let mut metrics = Metrics::default();
+        for result in iter {
+            metrics.method = metrics.method.max(result.method);
+            metrics.difference += result.difference;
+            metrics.drift = metrics.drift.max(result.drift);
+            metrics.iterations += result.iterations;
+            metrics.matched_nodes += result.matched_nodes;
+            metrics.results.push(result);
+        }
+        metrics
+    }
+}
+
+/// The metrics that can be requested like properties of the data set.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum MetricProperty {
+    DeviceId,
+    Difference,
+    Drift,
+    Iterations,
+    MatchedNodes,
+    Method,
+}
+
+impl MetricProperty {
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        [
+            ("DeviceId", Self::DeviceId),
+            ("Difference", Self::Difference),
+            ("Drift", Self::Drift),
+            ("Iterations", Self::Iterations),
+            ("MatchedNodes", Self::MatchedNodes),
+            ("Method", Self::Method),
+        ]
+        .into_iter()
+        .find(|(metric, _)| metric.eq_ignore_ascii_case(name))
+        .map(|(_, metric)| metric)
+    }
+
+    pub(crate) fn value_type(self) -> ValueType {
+        match self {
+            Self::DeviceId | Self::Method => ValueType::String,
+            _ => ValueType::Integer,
+        }
+    }
+
+    /// The value of integer metrics.
+    pub(crate) fn integer(self, metrics: &Metrics) -> Option<i32> {
+        match self {
+            Self::Difference => Some(metrics.difference),
+            Self::Drift => Some(metrics.drift),
+            Self::Iterations => Some(metrics.iterations),
+            Self::MatchedNodes => Some(metrics.matched_nodes),
+            Self::DeviceId | Self::Method => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aggregated_metrics() {
+        assert!(MatchMethod::None < MatchMethod::Performance);
+        assert!(MatchMethod::Performance < MatchMethod::Combined);
+        assert!(MatchMethod::Combined < MatchMethod::Predictive);
+
+        let result = |method, difference, drift, matched_nodes| ResultMetrics {
+            method,
+            difference,
+            drift,
+            iterations: 1,
+            matched_nodes,
+            ..ResultMetrics::default()
+        };
+        let metrics: Metrics = [
+            result(MatchMethod::Performance, 0, 1, 4),
+            result(MatchMethod::None, 0, 0, 0),
+            result(MatchMethod::Predictive, 3, 2, 2),
+            result(MatchMethod::Combined, 1, 0, 1),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(metrics.results.len(), 4);
+        assert_eq!(metrics.method, MatchMethod::Predictive);
+        assert_eq!(metrics.difference, 4);
+        assert_eq!(metrics.drift, 2);
+        assert_eq!(metrics.iterations, 4);
+        assert_eq!(metrics.matched_nodes, 7);
+
+        let unmatched: Metrics = [result(MatchMethod::None, 0, 0, 0)].into_iter().collect();
+        assert_eq!(unmatched.method, MatchMethod::None);
+    }
+}
@@ -0,0 +1,227 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use http::{Request, Response};
+use pin_project_lite::pin_project;
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::{
+    device_info::DeviceInfo, evidence::Evidence, hash_engine::HashEngine, response::ResponseHeaders,
+};
+
+/// A tower layer that runs the device detection for every request and inserts the
+/// `DeviceInfo` of the detection into the request extensions.
+///
+/// The evidence is built with `Evidence::from_request`. If the engine fails to process
+/// it, the request is passed on without a `DeviceInfo`.
+/// The detection runs on the thread polling the service, which is fast for the in memory
+/// configs, but blocks on file reads for the configs that do not load the whole data set.
+#[derive(Clone)]
+pub struct DeviceDetectionLayer {
+    engine: Arc<HashEngine>,
+    config: Arc<LayerConfig>,
+}
+
+#[derive(Clone, Default)]
+struct LayerConfig {
+    properties: Option<Vec<String>>,
+    accept_ch: bool,
+}
+
+impl DeviceDetectionLayer {
+    /// Creates a layer that copies all properties of the results into the `DeviceInfo`.
+    pub fn new(engine: Arc<HashEngine>) -> Self {
+        Self {
+            engine,
+            config: Arc::default(),
+        }
+    }
+
+    /// Limits the properties copied into the `DeviceInfo`.
+    pub fn properties<I, S>(mut self, properties: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Arc::make_mut(&mut self.config).properties = Some(
+            properties
+                .into_iter()
+                .map(|property| property.as_ref().to_string())
+                .collect(),
+        );
+        self
+    }
+
+    /// Appends the `Accept-CH` headers of `ResultsHash::response_headers` to the responses,
+    /// so the browser sends the client hints the engine can use with its next request.
+    pub fn accept_ch(mut self, enabled: bool) -> Self {
+        Arc::make_mut(&mut self.config).accept_ch = enabled;
+        self
+    }
+}
+
+impl<S> Layer<S> for DeviceDetectionLayer {
+    type Service = DeviceDetection<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        DeviceDetection {
+            inner,
+            engine: Arc::clone(&self.engine),
+            config: Arc::clone(&self.config),
+        }
+    }
+}
+
+/// The service created by `DeviceDetectionLayer`.
+#[derive(Clone)]
+pub struct DeviceDetection<S> {
+    inner: S,
+    engine: Arc<HashEngine>,
+    config: Arc<LayerConfig>,
+}
+
+impl<S> DeviceDetection<S> {
+    fn detect<B>(&self, request: &Request<B>) -> Option<(DeviceInfo, ResponseHeaders)> {
+        let evidence = Evidence::from_request(request);
+        let mut results = self.engine.process(&evidence).ok()?;
+
+        let info = match &self.config.properties {
+            Some(properties) => results.to_owned_result(properties),
+            None => results.to_owned_result(results.selected_properties()),
+        };
+        let headers = if self.config.accept_ch {
+            results.response_headers()
+        } else {
+            ResponseHeaders::default()
+        };
+        Some((info, headers))
+    }
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for DeviceDetection<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<ReqBody>) -> Self::Future {
+        let headers = match self.detect(&request) {
+            Some((info, headers)) => {
+                request.extensions_mut().insert(info);
+                headers
+            }
+            None => ResponseHeaders::default(),
+        };
+
+        ResponseFuture {
+            inner: self.inner.call(request),
+            headers,
+        }
+    }
+}
+
+pin_project! {
+    /// The response future of `DeviceDetection`, appends the response headers.
+    pub struct ResponseFuture<F> {
+        #[pin]
+        inner: F,
+        headers: ResponseHeaders,
+    }
+}
+
+impl<F, ResBody, E> Future for ResponseFuture<F>
+where
+    F: Future<Output = Result<Response<ResBody>, E>>,
+{
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let mut response = match this.inner.poll(cx) {
+            Poll::Ready(Ok(response)) => response,
+            other => return other,
+        };
+
+        for (name, value) in this.headers.to_header_map() {
+            if let Some(name) = name {
+                response.headers_mut().append(name, value);
+            }
+        }
+        Poll::Ready(Ok(response))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{future::Ready, task::Waker};
+
+    use crate::{
+        hash_engine::HashConfig,
+        test_support::{self, IPHONE_UA},
+    };
+
+    use super::*;
+
+    struct Echo;
+
+    impl Service<Request<()>> for Echo {
+        type Response = Response<Option<DeviceInfo>>;
+        type Error = ();
+        type Future = Ready<Result<Self::Response, ()>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), ()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, request: Request<()>) -> Self::Future {
+            std::future::ready(Ok(Response::new(
+                request.extensions().get::<DeviceInfo>().cloned(),
+            )))
+        }
+    }
+
+    #[test]
+    fn detection_layer() {
+        let engine = Arc::new(test_support::engine(HashConfig::HighPerformance));
+        let mut service = DeviceDetectionLayer::new(Arc::clone(&engine))
+            .properties(["IsMobile", "PlatformName"])
+            .accept_ch(true)
+            .layer(Echo);
+
+        let request = Request::builder()
+            .header("user-agent", IPHONE_UA)
+            .body(())
+            .unwrap();
+        let expected = engine
+            .process(&Evidence::from_request(&request))
+            .unwrap()
+            .response_headers()
+            .to_header_map();
+
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut future = std::pin::pin!(service.call(request));
+        let Poll::Ready(Ok(response)) = future.as_mut().poll(&mut cx) else {
+            panic!("the echo service responds immediately");
+        };
+
+        assert_eq!(response.headers(), &expected);
+
+        let info = response
+            .into_body()
+            .expect("the request to contain the device info");
+        assert_eq!(info.get_str("PlatformName"), Some("iOS"));
+        assert_eq!(info.get_bool("IsMobile"), Some(true));
+        assert_eq!(info.get_str("BrowserName"), None);
+    }
+}
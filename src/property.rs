@@ -0,0 +1,78 @@
+use std::ffi::CStr;
+
+/// A property resolved once against the data set of the engine, see `HashEngine::property`.
+/// Requesting values with a handle skips the lookup of the property by its name.
+/// Handles stay usable after the engine reloaded its data, if the property moved
+/// in the new data set it is looked up by name again.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PropertyHandle {
+    name: String,
+    index: i32,
+}
+
+impl PropertyHandle {
+    pub(crate) fn new(name: String, index: i32) -> Self {
+        Self { name, index }
+    }
+
+    /// The name of the property as declared in the data set.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the required property index if it still refers to the property
+    /// in the data set, whose name for the index is given.
+    pub(crate) fn index_for(&self, name_at_index: Option<&CStr>) -> Option<i32> {
+        name_at_index
+            .filter(|name| name.to_bytes() == self.name.as_bytes())
+            .map(|_| self.index)
+    }
+
+    pub(crate) fn index(&self) -> i32 {
+        self.index
+    }
+}
+
+/// Identifies a property requested from the results, either by its name or by a
+/// pre-resolved `PropertyHandle`.
+pub trait PropertyKey {
+    /// The name of the requested property.
+    fn property_name(&self) -> &str;
+
+    /// The pre-resolved handle of the property, if there is one.
+    fn handle(&self) -> Option<&PropertyHandle> {
+        None
+    }
+}
+
+impl PropertyKey for str {
+    fn property_name(&self) -> &str {
+        self
+    }
+}
+
+impl PropertyKey for String {
+    fn property_name(&self) -> &str {
+        self
+    }
+}
+
+impl PropertyKey for PropertyHandle {
+    fn property_name(&self) -> &str {
+        &self.name
+    }
+
+    fn handle(&self) -> Option<&PropertyHandle> {
+        Some(self)
+    }
+}
+
+impl<T: PropertyKey + ?Sized> PropertyKey for &T {
+    fn property_name(&self) -> &str {
+        (**self).property_name()
+    }
+
+    fn handle(&self) -> Option<&PropertyHandle> {
+        (**self).handle()
+    }
+}
@@ -0,0 +1,180 @@
+/// The prefix of the properties that contain the values of response headers,
+/// like `SetHeaderBrowserAccept-CH`.
+pub(crate) const SET_HEADER_PREFIX: &str = "SetHeader";
+
+/// Response headers requesting client hints from the browser, aggregated from the
+/// `SetHeader*` properties of the results, see `ResultsHash::response_headers`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ResponseHeaders {
+    // header names with their values, in the order they were first seen
+    headers: Vec<(String, Vec<String>)>,
+}
+
+impl ResponseHeaders {
+    pub const ACCEPT_CH: &str = "Accept-CH";
+    pub const CRITICAL_CH: &str = "Critical-CH";
+
+    /// Adds the value of a `SetHeader*` property, other properties are ignored.
+    /// The values are a comma separated list, `Unknown` values are skipped.
+    pub(crate) fn add_property(&mut self, property: &str, value: &str) {
+        if let Some(header) = header_name(property) {
+            self.add(header, value.split(','));
+        }
+    }
+
+    fn add<'a>(&mut self, header: &str, values: impl IntoIterator<Item = &'a str>) {
+        let index = match self
+            .headers
+            .iter()
+            .position(|(name, _)| name.eq_ignore_ascii_case(header))
+        {
+            Some(index) => index,
+            None => {
+                self.headers.push((header.to_string(), Vec::default()));
+                self.headers.len() - 1
+            }
+        };
+
+        let existing = &mut self.headers[index].1;
+        for value in values.into_iter().map(str::trim) {
+            if value.is_empty()
+                || value.eq_ignore_ascii_case("Unknown")
+                || existing
+                    .iter()
+                    .any(|known| known.eq_ignore_ascii_case(value))
+            {
+                continue;
+            }
+            existing.push(value.to_string());
+        }
+        if existing.is_empty() {
+            self.headers.remove(index);
+        }
+    }
+
+    /// Marks the client hints as critical, the browser retries the request with them if
+    /// they were missing. Only hints that are part of `Accept-CH` are added to `Critical-CH`.
+    pub fn with_critical_ch<I, S>(mut self, hints: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let accepted = self.get(Self::ACCEPT_CH).unwrap_or_default().to_vec();
+        let critical: Vec<String> = hints
+            .into_iter()
+            .filter_map(|hint| {
+                accepted
+                    .iter()
+                    .find(|accepted| accepted.eq_ignore_ascii_case(hint.as_ref()))
+                    .cloned()
+            })
+            .collect();
+        self.add(Self::CRITICAL_CH, critical.iter().map(String::as_str));
+        self
+    }
+
+    /// Returns the values of a header.
+    pub fn get(&self, header: &str) -> Option<&[String]> {
+        self.headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(header))
+            .map(|(_, values)| values.as_slice())
+    }
+
+    /// The values of the `Accept-CH` header.
+    pub fn accept_ch(&self) -> &[String] {
+        self.get(Self::ACCEPT_CH).unwrap_or_default()
+    }
+
+    /// The values of the `Critical-CH` header.
+    pub fn critical_ch(&self) -> &[String] {
+        self.get(Self::CRITICAL_CH).unwrap_or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.headers.is_empty()
+    }
+
+    /// Iterates over the headers with their values joined, ready to be sent.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, String)> {
+        self.headers
+            .iter()
+            .map(|(name, values)| (name.as_str(), values.join(", ")))
+    }
+
+    /// Converts the headers into a header map, headers that are not valid HTTP headers
+    /// are skipped.
+    #[cfg(feature = "http")]
+    pub fn to_header_map(&self) -> http::HeaderMap {
+        self.iter()
+            .filter_map(|(name, value)| {
+                Some((
+                    http::HeaderName::try_from(name).ok()?,
+                    http::HeaderValue::try_from(value).ok()?,
+                ))
+            })
+            .collect()
+    }
+}
+
+/// Extracts the header name from the name of a `SetHeader*` property. The name of the
+/// component follows the prefix, the header name starts with the next upper case letter,
+/// e.g. `SetHeaderBrowserAccept-CH` sets `Accept-CH`.
+fn header_name(property: &str) -> Option<&str> {
+    let rest = property.strip_prefix(SET_HEADER_PREFIX)?;
+    let start = rest
+        .char_indices()
+        .skip(1)
+        .find(|(_, c)| c.is_ascii_uppercase())
+        .map(|(index, _)| index)?;
+    Some(&rest[start..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aggregate_headers() {
+        assert_eq!(header_name("SetHeaderBrowserAccept-CH"), Some("Accept-CH"));
+        assert_eq!(header_name("SetHeaderPlatformAccept-CH"), Some("Accept-CH"));
+        assert_eq!(header_name("SetHeader"), None);
+        assert_eq!(header_name("BrowserName"), None);
+
+        let mut headers = ResponseHeaders::default();
+        headers.add_property(
+            "SetHeaderBrowserAccept-CH",
+            "Sec-CH-UA,Sec-CH-UA-Full-Version-List",
+        );
+        headers.add_property("SetHeaderHardwareAccept-CH", "Sec-CH-UA-Model,sec-ch-ua");
+        headers.add_property("SetHeaderPlatformAccept-CH", "Unknown");
+        headers.add_property("IsMobile", "True");
+
+        assert_eq!(
+            headers.accept_ch(),
+            [
+                "Sec-CH-UA",
+                "Sec-CH-UA-Full-Version-List",
+                "Sec-CH-UA-Model"
+            ]
+        );
+        assert!(headers.critical_ch().is_empty());
+
+        let headers = headers.with_critical_ch(["sec-ch-ua-model", "Sec-CH-UA-Arch"]);
+        assert_eq!(headers.critical_ch(), ["Sec-CH-UA-Model"]);
+        assert_eq!(
+            headers.iter().collect::<Vec<_>>(),
+            [
+                (
+                    "Accept-CH",
+                    "Sec-CH-UA, Sec-CH-UA-Full-Version-List, Sec-CH-UA-Model".to_string()
+                ),
+                ("Critical-CH", "Sec-CH-UA-Model".to_string())
+            ]
+        );
+
+        let mut unknown = ResponseHeaders::default();
+        unknown.add_property("SetHeaderPlatformAccept-CH", "Unknown");
+        assert!(unknown.is_empty());
+    }
+}
@@ -0,0 +1,45 @@
+use std::path::PathBuf;
+
+use crate::{
+    evidence::{Evidence, EvidenceKind},
+    hash_engine::{HashConfigBuilder, HashEngine, HashEngineBuilder},
+};
+
+/// The Lite data file of the device detection submodule.
+pub(crate) const DATA_FILE: &str =
+    "device-detection-cxx/device-detection-data/51Degrees-LiteV4.1.hash";
+
+/// Safari 16.2 on an iPhone, detected as `iOS` 16.2.
+pub(crate) const IPHONE_UA: &str = "Mozilla/5.0 (iPhone; CPU iPhone OS 16_2 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/16.2 Mobile/15E148 Safari/604.1";
+
+/// Chrome 98 on Windows 10, a browser that supports client hints.
+pub(crate) const CHROME_UA: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/98.0.4758.102 Safari/537.36";
+
+pub(crate) fn data_file() -> PathBuf {
+    DATA_FILE.into()
+}
+
+/// A builder for the Lite data file with the given config.
+pub(crate) fn builder<C: Into<HashConfigBuilder>>(config: C) -> HashEngineBuilder {
+    HashEngineBuilder::new(&data_file()).hash_config(config)
+}
+
+/// An engine for the Lite data file with all properties.
+pub(crate) fn engine<C: Into<HashConfigBuilder>>(config: C) -> HashEngine {
+    builder(config).init().unwrap()
+}
+
+/// Evidence with the user agent of `IPHONE_UA`.
+pub(crate) fn iphone() -> Evidence {
+    Evidence::try_new_with_user_agent(IPHONE_UA).unwrap()
+}
+
+/// Evidence with the given values, in order.
+pub(crate) fn evidence(values: &[(EvidenceKind, &str, &str)]) -> Evidence {
+    values
+        .iter()
+        .try_fold(Evidence::default(), |evidence, (kind, field, value)| {
+            evidence.try_add(*kind, field, value)
+        })
+        .unwrap()
+}
@@ -0,0 +1,275 @@
+use std::{error::Error, ffi::CStr, fmt::Display, str::FromStr};
+
+use crate::fiftyone_degrees::{
+    self,
+    e_fiftyone_degrees_results_no_value_reason_FIFTYONE_DEGREES_RESULTS_NO_VALUE_REASON_DIFFERENCE as REASON_DIFFERENCE,
+    e_fiftyone_degrees_results_no_value_reason_FIFTYONE_DEGREES_RESULTS_NO_VALUE_REASON_INVALID_PROPERTY as REASON_INVALID_PROPERTY,
+    e_fiftyone_degrees_results_no_value_reason_FIFTYONE_DEGREES_RESULTS_NO_VALUE_REASON_NO_MATCHED_NODES as REASON_NO_MATCHED_NODES,
+    e_fiftyone_degrees_results_no_value_reason_FIFTYONE_DEGREES_RESULTS_NO_VALUE_REASON_NO_RESULT_FOR_PROPERTY as REASON_NO_RESULT_FOR_PROPERTY,
+    e_fiftyone_degrees_results_no_value_reason_FIFTYONE_DEGREES_RESULTS_NO_VALUE_REASON_NO_RESULTS as REASON_NO_RESULTS,
+    e_fiftyone_degrees_results_no_value_reason_FIFTYONE_DEGREES_RESULTS_NO_VALUE_REASON_NULL_PROFILE as REASON_NULL_PROFILE,
+    e_fiftyone_degrees_results_no_value_reason_FIFTYONE_DEGREES_RESULTS_NO_VALUE_REASON_TOO_MANY_VALUES as REASON_TOO_MANY_VALUES,
+    e_fiftyone_degrees_results_no_value_reason_FIFTYONE_DEGREES_RESULTS_NO_VALUE_REASON_UNKNOWN as REASON_UNKNOWN,
+    fiftyoneDegreesResultsNoValueReason,
+};
+
+/// The type of the values of a property, as declared in the data set.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValueType {
+    String,
+    Integer,
+    Double,
+    Boolean,
+    JavaScript,
+    SinglePrecisionFloat,
+    SingleByte,
+    /// A value type not known to this wrapper.
+    Other(u8),
+}
+
+impl ValueType {
+    pub(crate) fn from_raw(value_type: u8) -> Self {
+        match value_type as u32 {
+            fiftyone_degrees::e_fiftyone_degrees_property_value_type_FIFTYONE_DEGREES_PROPERTY_VALUE_TYPE_STRING => Self::String,
+            fiftyone_degrees::e_fiftyone_degrees_property_value_type_FIFTYONE_DEGREES_PROPERTY_VALUE_TYPE_INTEGER => Self::Integer,
+            fiftyone_degrees::e_fiftyone_degrees_property_value_type_FIFTYONE_DEGREES_PROPERTY_VALUE_TYPE_DOUBLE => Self::Double,
+            fiftyone_degrees::e_fiftyone_degrees_property_value_type_FIFTYONE_DEGREES_PROPERTY_VALUE_TYPE_BOOLEAN => Self::Boolean,
+            fiftyone_degrees::e_fiftyone_degrees_property_value_type_FIFTYONE_DEGREES_PROPERTY_VALUE_TYPE_JAVASCRIPT => Self::JavaScript,
+            fiftyone_degrees::e_fiftyone_degrees_property_value_type_FIFTYONE_DEGREES_PROPERTY_VALUE_TYPE_SINGLE_PRECISION_FLOAT => Self::SinglePrecisionFloat,
+            fiftyone_degrees::e_fiftyone_degrees_property_value_type_FIFTYONE_DEGREES_PROPERTY_VALUE_TYPE_SINGLE_BYTE => Self::SingleByte,
+            _ => Self::Other(value_type),
+        }
+    }
+}
+
+impl Display for ValueType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValueType::String => f.write_str("string"),
+            ValueType::Integer => f.write_str("integer"),
+            ValueType::Double => f.write_str("double"),
+            ValueType::Boolean => f.write_str("boolean"),
+            ValueType::JavaScript => f.write_str("javascript"),
+            ValueType::SinglePrecisionFloat => f.write_str("single precision float"),
+            ValueType::SingleByte => f.write_str("single byte"),
+            ValueType::Other(raw) => write!(f, "unknown value type {}", raw),
+        }
+    }
+}
+
+/// A version number like `16.2` or `98.0.4758.102`.
+/// Missing parts are `None`, so `16.2` is lower than `16.2.0`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Version {
+    pub major: u32,
+    pub minor: Option<u32>,
+    pub patch: Option<u32>,
+    pub build: Option<u32>,
+}
+
+impl FromStr for Version {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.trim().split('.');
+        let major = parts.next().unwrap_or_default().parse()?;
+        let mut next =
+            || -> Result<Option<u32>, Self::Err> { parts.next().map(str::parse).transpose() };
+
+        Ok(Self {
+            major,
+            minor: next()?,
+            patch: next()?,
+            build: next()?,
+        })
+    }
+}
+
+impl Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.major)?;
+        for part in [self.minor, self.patch, self.build].into_iter().flatten() {
+            write!(f, ".{}", part)?;
+        }
+        Ok(())
+    }
+}
+
+/// The reasons the results contain no value for a property.
+/// Apart from `UnknownProperty` and `PropertyNotSelected` these mirror the reasons
+/// reported by the 51Degrees library, the `Display` impl carries the library's message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NoValueReason {
+    /// The property is not part of the data set.
+    UnknownProperty,
+    /// The property is part of the data set, but was not selected with `set_properties`.
+    PropertyNotSelected,
+    /// The difference of the match exceeded the configured limit.
+    Difference,
+    /// No hash nodes were matched for the evidence.
+    NoMatchedNodes,
+    /// The property index is not valid for the results.
+    InvalidProperty,
+    /// None of the results contain a value for the property's component.
+    NoResultForProperty,
+    /// The results are empty, e.g. because no evidence was provided.
+    NoResults,
+    /// The property has too many values to return them all.
+    TooManyValues,
+    /// The results contain a null profile for the property's component.
+    NullProfile,
+    /// The reason is not known.
+    Unknown,
+}
+
+impl NoValueReason {
+    pub(crate) fn from_raw(reason: fiftyoneDegreesResultsNoValueReason) -> Self {
+        match reason {
+            REASON_DIFFERENCE => Self::Difference,
+            REASON_NO_MATCHED_NODES => Self::NoMatchedNodes,
+            REASON_INVALID_PROPERTY => Self::InvalidProperty,
+            REASON_NO_RESULT_FOR_PROPERTY => Self::NoResultForProperty,
+            REASON_NO_RESULTS => Self::NoResults,
+            REASON_TOO_MANY_VALUES => Self::TooManyValues,
+            REASON_NULL_PROFILE => Self::NullProfile,
+            _ => Self::Unknown,
+        }
+    }
+
+    fn to_raw(self) -> Option<fiftyoneDegreesResultsNoValueReason> {
+        match self {
+            Self::UnknownProperty | Self::PropertyNotSelected => None,
+            Self::Difference => Some(REASON_DIFFERENCE),
+            Self::NoMatchedNodes => Some(REASON_NO_MATCHED_NODES),
+            Self::InvalidProperty => Some(REASON_INVALID_PROPERTY),
+            Self::NoResultForProperty => Some(REASON_NO_RESULT_FOR_PROPERTY),
+            Self::NoResults => Some(REASON_NO_RESULTS),
+            Self::TooManyValues => Some(REASON_TOO_MANY_VALUES),
+            Self::NullProfile => Some(REASON_NULL_PROFILE),
+            Self::Unknown => Some(REASON_UNKNOWN),
+        }
+    }
+}
+
+impl Display for NoValueReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.to_raw() {
+            None if *self == Self::UnknownProperty => {
+                f.write_str("The property is not part of the data set.")
+            }
+            None => f.write_str(
+                "The property is part of the data set, but was not selected when the engine was built.",
+            ),
+            Some(reason) => {
+                // the library returns static strings, nothing to free here
+                let msg = unsafe {
+                    CStr::from_ptr(
+                        fiftyone_degrees::fiftyoneDegreesResultsHashGetNoValueReasonMessage(
+                            reason,
+                        ),
+                    )
+                };
+                f.write_str(msg.to_str().unwrap_or("error formatting no value reason"))
+            }
+        }
+    }
+}
+
+impl Error for NoValueReason {}
+
+/// The reasons a typed value could not be read from the results.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ValueError {
+    /// The results contain no value for the property.
+    NoValue(NoValueReason),
+    /// The data set declares a different value type for the property.
+    TypeMismatch {
+        expected: ValueType,
+        found: ValueType,
+    },
+    /// The value could not be parsed into the requested type.
+    Parse { value: String, expected: ValueType },
+}
+
+impl Display for ValueError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValueError::NoValue(reason) => {
+                write!(
+                    f,
+                    "the results contain no value for the property: {}",
+                    reason
+                )
+            }
+            ValueError::TypeMismatch { expected, found } => write!(
+                f,
+                "expected a property of type {}, the property is of type {}.",
+                expected, found
+            ),
+            ValueError::Parse { value, expected } => {
+                write!(f, "error parsing '{}' as {}.", value, expected)
+            }
+        }
+    }
+}
+
+impl Error for ValueError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ValueError::NoValue(reason) => Some(reason),
+            _ => None,
+        }
+    }
+}
+
+impl From<NoValueReason> for ValueError {
+    fn from(reason: NoValueReason) -> Self {
+        ValueError::NoValue(reason)
+    }
+}
+
+pub(crate) fn parse_bool(value: &str) -> Result<bool, ValueError> {
+    match value {
+        "True" | "true" => Ok(true),
+        "False" | "false" => Ok(false),
+        _ => Err(ValueError::Parse {
+            value: value.to_string(),
+            expected: ValueType::Boolean,
+        }),
+    }
+}
+
+pub(crate) fn parse<T: FromStr>(value: &str, expected: ValueType) -> Result<T, ValueError> {
+    value.trim().parse().map_err(|_| ValueError::Parse {
+        value: value.to_string(),
+        expected,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_versions() {
+        let version: Version = "98.0.4758.102".parse().unwrap();
+        assert_eq!(
+            version,
+            Version {
+                major: 98,
+                minor: Some(0),
+                patch: Some(4758),
+                build: Some(102)
+            }
+        );
+        assert_eq!(version.to_string(), "98.0.4758.102");
+
+        let version: Version = "16.2".parse().unwrap();
+        assert_eq!(version.to_string(), "16.2");
+        assert!(version < "16.10".parse().unwrap());
+
+        assert!("Unknown".parse::<Version>().is_err());
+    }
+}